@@ -0,0 +1,446 @@
+//! A minimal read-only HTTP surface over `BlockLog` and `Store`, so an
+//! operator or dashboard can inspect a running node's chain and rebuilt
+//! state without reaching into the data directory by hand. Route dispatch
+//! (`route`) and the handlers it calls are plain functions so they can be
+//! exercised without a live socket; `AdminServer::serve`, the only part
+//! that actually binds a port, is the sole piece gated behind the
+//! `admin-api` feature — most builds of the node shouldn't pay for an
+//! always-on accept loop.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use thiserror::Error;
+
+use crate::block::Block;
+use crate::log::{BlockLog, LogError};
+use crate::state::State;
+use crate::store::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct AdminConfig {
+    pub bind_addr: String,
+    pub log_path: String,
+    pub state_path: String,
+    pub snapshot_dir: String,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        AdminConfig {
+            bind_addr: "127.0.0.1:8090".to_string(),
+            log_path: crate::log::LOG_PATH.to_string(),
+            state_path: crate::store::STATE_PATH.to_string(),
+            snapshot_dir: crate::log::SNAPSHOT_DIR.to_string(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Log error: {0}")]
+    Log(#[from] LogError),
+
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+
+    #[error("Serialization error: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("not found: {0}")]
+    NotFound(String),
+}
+
+/// Maps an `AdminError` to the HTTP status code a client should see —
+/// `LogError::EmptyLog` and a missing block/account read as "nothing
+/// there" (404), while a root mismatch or broken hash chain reads as the
+/// node's own data being wrong (500) rather than the caller's fault.
+fn status_for(error: &AdminError) -> u16 {
+    match error {
+        AdminError::NotFound(_) => 404,
+        AdminError::Log(LogError::EmptyLog) => 404,
+        AdminError::Log(_) => 500,
+        AdminError::Store(_) => 500,
+        AdminError::Serialize(_) => 500,
+        AdminError::Io(_) => 500,
+    }
+}
+
+/// Shared counters exposed at `/metrics`. Block height and tx count are
+/// cheap to recompute from the log on every scrape; the one thing that
+/// can't be recomputed on the spot is how long the last state replay
+/// took, so that's the only piece of actual server state here.
+#[derive(Default)]
+pub struct Metrics {
+    last_replay: Mutex<Option<Duration>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    fn record_replay(&self, elapsed: Duration) {
+        *self.last_replay.lock().unwrap() = Some(elapsed);
+    }
+}
+
+fn rebuild_timed(config: &AdminConfig, metrics: &Metrics) -> Result<State, AdminError> {
+    let start = Instant::now();
+    let state = BlockLog::rebuild_state_from_snapshot(
+        &config.log_path,
+        &config.snapshot_dir,
+        &config.state_path,
+    )?;
+    metrics.record_replay(start.elapsed());
+    Ok(state)
+}
+
+fn handle_blocks(config: &AdminConfig, query: &HashMap<String, String>) -> Result<String, AdminError> {
+    let from: u64 = query.get("from").and_then(|v| v.parse().ok()).unwrap_or(0);
+    let to: u64 = query.get("to").and_then(|v| v.parse().ok()).unwrap_or(u64::MAX);
+
+    let mut blocks = Vec::new();
+    for block in BlockLog::iter_from(&config.log_path) {
+        let block = block?;
+        if block.number > to {
+            break;
+        }
+        if block.number >= from {
+            blocks.push(block);
+        }
+    }
+
+    Ok(serde_json::to_string(&blocks)?)
+}
+
+fn handle_block(config: &AdminConfig, number_str: &str) -> Result<String, AdminError> {
+    let number: u64 = number_str
+        .parse()
+        .map_err(|_| AdminError::NotFound(format!("block {}", number_str)))?;
+
+    for block in BlockLog::iter_from(&config.log_path) {
+        let block = block?;
+        if block.number == number {
+            return Ok(serde_json::to_string(&block)?);
+        }
+    }
+
+    Err(AdminError::NotFound(format!("block {}", number)))
+}
+
+fn handle_head(config: &AdminConfig) -> Result<String, AdminError> {
+    let latest = BlockLog::latest_from(&config.log_path)?
+        .ok_or_else(|| AdminError::NotFound("no blocks".to_string()))?;
+
+    Ok(json!({
+        "number": latest.number,
+        "hash": latest.hash,
+        "state_root": latest.state_root,
+    })
+    .to_string())
+}
+
+fn handle_state(config: &AdminConfig, metrics: &Metrics) -> Result<String, AdminError> {
+    let state = rebuild_timed(config, metrics)?;
+    Ok(serde_json::to_string(&state)?)
+}
+
+fn handle_account(config: &AdminConfig, address: &str, metrics: &Metrics) -> Result<String, AdminError> {
+    let state = rebuild_timed(config, metrics)?;
+
+    let balances: HashMap<String, u128> = state
+        .balances
+        .iter()
+        .filter(|(key, _)| key.address == address)
+        .map(|(key, amount)| (key.asset_id.clone(), *amount))
+        .collect();
+
+    Ok(json!({
+        "address": address,
+        "nonce": state.get_nonce(address),
+        "balances": balances,
+    })
+    .to_string())
+}
+
+fn handle_metrics(config: &AdminConfig, metrics: &Metrics) -> String {
+    let (height, tx_count) = match BlockLog::read_all_from(&config.log_path) {
+        Ok(blocks) => (
+            blocks.last().map(|b| b.number).unwrap_or(0),
+            blocks.iter().map(|b| b.txs.len() as u64).sum::<u64>(),
+        ),
+        Err(_) => (0, 0),
+    };
+    let last_replay_ms = metrics
+        .last_replay
+        .lock()
+        .unwrap()
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    format!(
+        "# HELP fvl_block_height Highest block number in the log.\n\
+         # TYPE fvl_block_height gauge\n\
+         fvl_block_height {}\n\
+         # HELP fvl_tx_count Total transactions recorded across the log.\n\
+         # TYPE fvl_tx_count counter\n\
+         fvl_tx_count {}\n\
+         # HELP fvl_last_replay_duration_ms Duration of the most recent state replay, in milliseconds.\n\
+         # TYPE fvl_last_replay_duration_ms gauge\n\
+         fvl_last_replay_duration_ms {}\n",
+        height, tx_count, last_replay_ms
+    )
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.trim_matches('/').split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// Dispatches `(method, path)` to the matching handler and renders its
+/// result (or error) as a `(status, body)` pair. Kept separate from
+/// `AdminServer::serve`'s socket handling so routing can be tested
+/// directly, without binding a port.
+pub(crate) fn route(
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+    config: &AdminConfig,
+    metrics: &Metrics,
+) -> (u16, String) {
+    let segments = split_path(path);
+    let result = match (method, segments.as_slice()) {
+        ("GET", ["blocks"]) => handle_blocks(config, query),
+        ("GET", ["block", number]) => handle_block(config, number),
+        ("GET", ["head"]) => handle_head(config),
+        ("GET", ["state"]) => handle_state(config, metrics),
+        ("GET", ["account", address]) => handle_account(config, address, metrics),
+        ("GET", ["metrics"]) => return (200, handle_metrics(config, metrics)),
+        _ => Err(AdminError::NotFound(format!("{} {}", method, path))),
+    };
+
+    match result {
+        Ok(body) => (200, body),
+        Err(e) => (status_for(&e), json!({ "error": e.to_string() }).to_string()),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    }
+}
+
+fn parse_target(target: &str) -> (String, HashMap<String, String>) {
+    match target.split_once('?') {
+        Some((path, query_str)) => (path.to_string(), parse_query(query_str)),
+        None => (target.to_string(), HashMap::new()),
+    }
+}
+
+fn parse_query(query_str: &str) -> HashMap<String, String> {
+    query_str
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+pub struct AdminServer;
+
+impl AdminServer {
+    /// Binds `config.bind_addr` and serves `/blocks`, `/block/{number}`,
+    /// `/head`, `/state`, `/account/{id}` and `/metrics` until the process
+    /// is killed. Handles one connection at a time — this is an admin/ops
+    /// surface, not something expected to take production query load.
+    #[cfg(feature = "admin-api")]
+    pub fn serve(config: AdminConfig) -> Result<(), AdminError> {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind(&config.bind_addr)?;
+        let metrics = Metrics::new();
+
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            let mut buf = [0u8; 8192];
+            let n = stream.read(&mut buf)?;
+            let request = String::from_utf8_lossy(&buf[..n]);
+
+            let Some(request_line) = request.lines().next() else {
+                continue;
+            };
+            let mut parts = request_line.split_whitespace();
+            let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+
+            let (path, query) = parse_target(target);
+            let (status, body) = route(method, &path, &query, &config, &metrics);
+
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                reason_phrase(status),
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+
+    fn test_paths(test_name: &str) -> AdminConfig {
+        AdminConfig {
+            bind_addr: "127.0.0.1:0".to_string(),
+            log_path: format!("data/test_admin_{}/blocks.log", test_name),
+            state_path: format!("data/test_admin_{}/state.json", test_name),
+            snapshot_dir: format!("data/test_admin_{}/snapshots", test_name),
+        }
+    }
+
+    fn cleanup(test_name: &str) {
+        let _ = std::fs::remove_dir_all(format!("data/test_admin_{}", test_name));
+    }
+
+    fn seed_chain(config: &AdminConfig) -> (Block, Block) {
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &config.log_path).unwrap();
+
+        let empty_root = State::new().state_root_hex();
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], empty_root);
+        BlockLog::append_to(&block1, &config.log_path).unwrap();
+
+        (genesis, block1)
+    }
+
+    #[test]
+    fn test_route_head_returns_latest_block() {
+        cleanup("head");
+        let config = test_paths("head");
+        seed_chain(&config);
+        let metrics = Metrics::new();
+
+        let (status, body) = route("GET", "/head", &HashMap::new(), &config, &metrics);
+        assert_eq!(status, 200);
+        assert!(body.contains("\"number\":1"));
+
+        cleanup("head");
+    }
+
+    #[test]
+    fn test_route_head_empty_log_returns_404() {
+        cleanup("head_empty");
+        let config = test_paths("head_empty");
+        let metrics = Metrics::new();
+
+        let (status, _) = route("GET", "/head", &HashMap::new(), &config, &metrics);
+        assert_eq!(status, 404);
+
+        cleanup("head_empty");
+    }
+
+    #[test]
+    fn test_route_block_not_found_returns_404() {
+        cleanup("block_missing");
+        let config = test_paths("block_missing");
+        seed_chain(&config);
+        let metrics = Metrics::new();
+
+        let (status, _) = route("GET", "/block/99", &HashMap::new(), &config, &metrics);
+        assert_eq!(status, 404);
+
+        cleanup("block_missing");
+    }
+
+    #[test]
+    fn test_route_blocks_respects_from_to() {
+        cleanup("blocks_range");
+        let config = test_paths("blocks_range");
+        seed_chain(&config);
+        let metrics = Metrics::new();
+
+        let mut query = HashMap::new();
+        query.insert("from".to_string(), "1".to_string());
+        query.insert("to".to_string(), "1".to_string());
+
+        let (status, body) = route("GET", "/blocks", &query, &config, &metrics);
+        assert_eq!(status, 200);
+        let blocks: Vec<Block> = serde_json::from_str(&body).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].number, 1);
+
+        cleanup("blocks_range");
+    }
+
+    #[test]
+    fn test_route_account_reports_balance() {
+        cleanup("account");
+        let config = test_paths("account");
+
+        let mut state = State::new();
+        state.set_balance(
+            "0x1234567890123456789012345678901234567890",
+            &crate::types::AssetType::Eth,
+            500,
+        );
+        let block0 = Block::new(0, "0x0".to_string(), vec![], state.state_root_hex());
+        BlockLog::append_to(&block0, &config.log_path).unwrap();
+        BlockLog::maybe_snapshot(&config.snapshot_dir, &state, &block0, 1).unwrap();
+        Store::save_to(&state, &config.state_path).unwrap();
+
+        let metrics = Metrics::new();
+        let (status, body) = route(
+            "GET",
+            "/account/0x1234567890123456789012345678901234567890",
+            &HashMap::new(),
+            &config,
+            &metrics,
+        );
+        assert_eq!(status, 200);
+        assert!(body.contains("\"eth\"") || body.contains("500"));
+
+        cleanup("account");
+    }
+
+    #[test]
+    fn test_route_unknown_path_returns_404() {
+        cleanup("unknown");
+        let config = test_paths("unknown");
+        let metrics = Metrics::new();
+
+        let (status, _) = route("GET", "/nope", &HashMap::new(), &config, &metrics);
+        assert_eq!(status, 404);
+
+        cleanup("unknown");
+    }
+
+    #[test]
+    fn test_metrics_format_reports_height_and_tx_count() {
+        cleanup("metrics");
+        let config = test_paths("metrics");
+        seed_chain(&config);
+        let metrics = Metrics::new();
+
+        let body = handle_metrics(&config, &metrics);
+        assert!(body.contains("fvl_block_height 1"));
+        assert!(body.contains("fvl_tx_count 0"));
+        assert!(body.contains("fvl_last_replay_duration_ms 0"));
+
+        cleanup("metrics");
+    }
+}
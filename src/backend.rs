@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::cache::{CacheMetrics, CacheMetricsSnapshot, LruCache};
+use crate::state::State;
+use crate::store::{Store, StoreError};
+
+/// A partial view of a single account, for key-scoped reads/writes that
+/// don't require pulling in the whole `State`.
+#[derive(Debug, Clone, Default)]
+pub struct AccountSnapshot {
+    pub nonce: u64,
+    pub balances: HashMap<String, u128>,
+}
+
+/// Abstraction over where `State` lives. `Store`'s `load_from`/`save_to`
+/// hardwire a single pretty-printed JSON file; implementing this trait lets
+/// the sequencer swap in other persistence (in-memory for tests, eventually
+/// an embedded KV store) without touching sequencer logic.
+pub trait StateBackend {
+    fn load(&self) -> Result<State, StoreError>;
+    fn save(&self, state: &State) -> Result<(), StoreError>;
+
+    /// Key-scoped read of a single account's nonce/balances.
+    fn get_account(&self, address: &str) -> Result<Option<AccountSnapshot>, StoreError> {
+        let state = self.load()?;
+        Ok(account_snapshot(&state, address))
+    }
+
+    /// Key-scoped write of a single account's nonce/balances, merged into
+    /// the backend's current state.
+    fn put_account(&self, address: &str, account: AccountSnapshot) -> Result<(), StoreError> {
+        let mut state = self.load()?;
+        state.nonces.insert(address.to_string(), account.nonce);
+        for (asset_id, amount) in account.balances {
+            let key = crate::state::BalanceKey {
+                address: address.to_string(),
+                asset_id,
+            };
+            state.balances.insert(key, amount);
+        }
+        self.save(&state)
+    }
+}
+
+fn account_snapshot(state: &State, address: &str) -> Option<AccountSnapshot> {
+    let nonce = *state.nonces.get(address)?;
+    let balances = state
+        .balances
+        .iter()
+        .filter(|(key, _)| key.address == address)
+        .map(|(key, amount)| (key.asset_id.clone(), *amount))
+        .collect();
+
+    Some(AccountSnapshot { nonce, balances })
+}
+
+/// The existing on-disk backend: one pretty-printed JSON file at a fixed path.
+pub struct JsonFileBackend {
+    path: String,
+}
+
+impl JsonFileBackend {
+    pub fn new(path: &str) -> Self {
+        JsonFileBackend { path: path.to_string() }
+    }
+}
+
+impl StateBackend for JsonFileBackend {
+    fn load(&self) -> Result<State, StoreError> {
+        Store::load_from(&self.path)
+    }
+
+    fn save(&self, state: &State) -> Result<(), StoreError> {
+        Store::save_to(state, &self.path)
+    }
+}
+
+/// Ephemeral, process-local backend for tests and one-off runs — no
+/// filesystem access.
+pub struct InMemoryBackend {
+    state: Mutex<State>,
+}
+
+impl InMemoryBackend {
+    pub fn new() -> Self {
+        InMemoryBackend { state: Mutex::new(State::new()) }
+    }
+
+    pub fn with_state(state: State) -> Self {
+        InMemoryBackend { state: Mutex::new(state) }
+    }
+}
+
+impl Default for InMemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateBackend for InMemoryBackend {
+    fn load(&self) -> Result<State, StoreError> {
+        Ok(self.state.lock().unwrap().clone())
+    }
+
+    fn save(&self, state: &State) -> Result<(), StoreError> {
+        *self.state.lock().unwrap() = state.clone();
+        Ok(())
+    }
+}
+
+/// LRU-cached read layer in front of any `StateBackend`, keyed by account
+/// address. Hot accounts are served from memory; a miss falls through to
+/// the inner backend's (possibly whole-file) load and promotes the result.
+pub struct CachedBackend<B: StateBackend> {
+    inner: B,
+    accounts: Mutex<LruCache<String, AccountSnapshot>>,
+    metrics: CacheMetrics,
+}
+
+impl<B: StateBackend> CachedBackend<B> {
+    pub fn new(inner: B, capacity: usize) -> Self {
+        CachedBackend {
+            inner,
+            accounts: Mutex::new(LruCache::new(capacity)),
+            metrics: CacheMetrics::default(),
+        }
+    }
+
+    pub fn metrics(&self) -> CacheMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+}
+
+impl<B: StateBackend> StateBackend for CachedBackend<B> {
+    fn load(&self) -> Result<State, StoreError> {
+        self.inner.load()
+    }
+
+    fn save(&self, state: &State) -> Result<(), StoreError> {
+        self.inner.save(state)?;
+        // The cache may now be stale for any address touched by this save;
+        // simplest correct thing is to drop it and let the next reads repopulate.
+        self.accounts.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn get_account(&self, address: &str) -> Result<Option<AccountSnapshot>, StoreError> {
+        if let Some(cached) = self.accounts.lock().unwrap().get(&address.to_string()) {
+            self.metrics.record_hit();
+            return Ok(Some(cached.clone()));
+        }
+
+        self.metrics.record_miss();
+        let account = self.inner.get_account(address)?;
+
+        if let Some(account) = &account {
+            self.accounts.lock().unwrap().put(address.to_string(), account.clone());
+        }
+
+        Ok(account)
+    }
+
+    fn put_account(&self, address: &str, account: AccountSnapshot) -> Result<(), StoreError> {
+        self.inner.put_account(address, account.clone())?;
+        self.accounts.lock().unwrap().put(address.to_string(), account);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AssetType;
+
+    #[test]
+    fn test_in_memory_backend_round_trip() {
+        let backend = InMemoryBackend::new();
+        let mut state = backend.load().unwrap();
+        state.set_balance("0xabc", &AssetType::Eth, 42);
+        backend.save(&state).unwrap();
+
+        let reloaded = backend.load().unwrap();
+        assert_eq!(reloaded.get_balance("0xabc", &AssetType::Eth), 42);
+    }
+
+    #[test]
+    fn test_in_memory_backend_account_snapshot() {
+        let backend = InMemoryBackend::new();
+        let mut state = backend.load().unwrap();
+        state.set_balance("0xabc", &AssetType::Eth, 10);
+        state.consume_nonce("0xabc", 0).unwrap();
+        backend.save(&state).unwrap();
+
+        let account = backend.get_account("0xabc").unwrap().unwrap();
+        assert_eq!(account.nonce, 1);
+        assert_eq!(account.balances.get("ETH"), Some(&10));
+    }
+
+    #[test]
+    fn test_get_account_missing_returns_none() {
+        let backend = InMemoryBackend::new();
+        assert!(backend.get_account("0xnobody").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cached_backend_serves_repeat_reads_from_cache() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 5);
+        state.consume_nonce("0xabc", 0).unwrap();
+
+        let backend = CachedBackend::new(InMemoryBackend::with_state(state), 4);
+
+        backend.get_account("0xabc").unwrap(); // miss, populates cache
+        backend.get_account("0xabc").unwrap(); // hit
+
+        let metrics = backend.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 1);
+    }
+
+    #[test]
+    fn test_cached_backend_invalidates_on_save() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 1);
+        state.consume_nonce("0xabc", 0).unwrap();
+
+        let backend = CachedBackend::new(InMemoryBackend::with_state(state), 4);
+        let first = backend.get_account("0xabc").unwrap().unwrap();
+        assert_eq!(first.balances.get("ETH"), Some(&1));
+
+        let mut updated = backend.load().unwrap();
+        updated.set_balance("0xabc", &AssetType::Eth, 99);
+        backend.save(&updated).unwrap();
+
+        let second = backend.get_account("0xabc").unwrap().unwrap();
+        assert_eq!(second.balances.get("ETH"), Some(&99));
+    }
+}
@@ -0,0 +1,22 @@
+use fvl_parser::admin::{AdminConfig, AdminServer};
+
+fn main() {
+    println!("=== FVL Admin API ===");
+
+    #[cfg(feature = "admin-api")]
+    {
+        let config = AdminConfig::default();
+        println!("Listening on {}", config.bind_addr);
+        if let Err(e) = AdminServer::serve(config) {
+            eprintln!("Admin server error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    #[cfg(not(feature = "admin-api"))]
+    {
+        eprintln!("Built without the `admin-api` feature; nothing to serve.");
+        eprintln!("   Rebuild with: cargo build --bin admin --features admin-api");
+        std::process::exit(1);
+    }
+}
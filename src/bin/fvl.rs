@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use fvl_parser::cli::output::Output;
 use fvl_parser::cli::repl::Repl;
 use fvl_parser::cli::commands::*;
 
@@ -23,6 +24,16 @@ enum Commands {
     Deploy {
         /// Path to YAML file
         yaml: String,
+        /// Deploy the named entry under the file's top-level `environments`
+        /// map instead of the base document (see `Parser::parse_yaml_env`),
+        /// so one YAML file can target multiple deployments
+        #[arg(long)]
+        env: Option<String>,
+        /// Only execute if the current state root still equals this value
+        /// (see `Transaction::expected_prior_root`). Guards a scripted batch
+        /// against silently running on top of a chain that's since moved on.
+        #[arg(long)]
+        expect_root: Option<String>,
     },
 
     /// Transfer assets between addresses
@@ -33,6 +44,15 @@ enum Commands {
         /// Asset type: ETH, ERC20:0x..., ERC721:0x...
         #[arg(default_value = "ETH")]
         asset: String,
+        /// Only execute if the current state root still equals this value
+        /// (see `Transaction::expected_prior_root`). Guards a scripted batch
+        /// against silently running on top of a chain that's since moved on.
+        #[arg(long)]
+        expect_root: Option<String>,
+        /// Simulate against a cloned state and print the resulting diff,
+        /// without appending a block to the log
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Interact with a deployed system
@@ -44,6 +64,19 @@ enum Commands {
         /// Action name (required for trigger and both modes)
         #[arg(long)]
         action: Option<String>,
+        /// Only execute if the current state root still equals this value
+        /// (see `Transaction::expected_prior_root`). Guards a scripted batch
+        /// against silently running on top of a chain that's since moved on.
+        #[arg(long)]
+        expect_root: Option<String>,
+        /// Simulate against a cloned state and print the resulting diff,
+        /// without appending a block to the log
+        #[arg(long)]
+        dry_run: bool,
+        /// Include the structured execution trace (see `runtime::TraceEvent`)
+        /// in the output
+        #[arg(long)]
+        trace: bool,
     },
 
     /// Update an oracle value
@@ -51,6 +84,24 @@ enum Commands {
         system_id: String,
         oracle_name: String,
         value: u128,
+        /// Named feed to post this update under (see `state::OracleSource`).
+        /// Defaults to `"primary"`; a different name registers a fallback
+        /// feed that's only read once the primary goes stale.
+        #[arg(long)]
+        source: Option<String>,
+        /// Only execute if the current state root still equals this value
+        /// (see `Transaction::expected_prior_root`). Guards a scripted batch
+        /// against silently running on top of a chain that's since moved on.
+        #[arg(long)]
+        expect_root: Option<String>,
+        /// Simulate against a cloned state and print the resulting diff,
+        /// without appending a block to the log
+        #[arg(long)]
+        dry_run: bool,
+        /// Include the structured execution trace (see `runtime::TraceEvent`)
+        /// in the output
+        #[arg(long)]
+        trace: bool,
     },
 
     /// Mint balance to an address (for testing)
@@ -90,8 +141,28 @@ enum StateCommands {
 
 #[derive(Subcommand)]
 enum ConfigCommands {
-    /// Set the sender address
-    SetSender { address: String },
+    /// Import a signing key (hex-encoded secp256k1 seed) and derive sender from it
+    SetSender { signing_key: String },
+    /// Select which block-log backend (see `blockstore::BlockStore`) future
+    /// commands persist through
+    SetStore {
+        /// file | s3
+        backend: String,
+        /// S3-compatible endpoint, e.g. http://localhost:9000 (s3 only)
+        #[arg(long)]
+        endpoint: Option<String>,
+        #[arg(long)]
+        region: Option<String>,
+        #[arg(long)]
+        bucket: Option<String>,
+        #[arg(long)]
+        access_key: Option<String>,
+        #[arg(long)]
+        secret_key: Option<String>,
+        /// Blocks per finalized segment object (s3 only)
+        #[arg(long, default_value = "1000")]
+        segment_size: u64,
+    },
     /// Show current config
     Show,
 }
@@ -100,29 +171,30 @@ fn main() {
     let cli = Cli::parse();
     let json = cli.json;
 
-    match cli.command {
+    let result = match cli.command {
         // No subcommand → launch REPL
         None => {
             let mut repl = Repl::new();
             repl.run();
+            Ok(())
         }
 
-        Some(Commands::Deploy { yaml }) => cmd_deploy(&yaml, json),
+        Some(Commands::Deploy { yaml, env, expect_root }) => cmd_deploy(&yaml, env.as_deref(), expect_root, json),
 
-        Some(Commands::Transfer { from, to, amount, asset }) => {
-            cmd_transfer(&from, &to, amount, &asset, json);
+        Some(Commands::Transfer { from, to, amount, asset, expect_root, dry_run }) => {
+            cmd_transfer(&from, &to, amount, &asset, expect_root, dry_run, json)
         }
 
-        Some(Commands::Interact { system_id, mode, action }) => {
-            cmd_interact(&system_id, &mode, action.as_deref(), json);
+        Some(Commands::Interact { system_id, mode, action, expect_root, dry_run, trace }) => {
+            cmd_interact(&system_id, &mode, action.as_deref(), expect_root, dry_run, trace, json)
         }
 
-        Some(Commands::OracleUpdate { system_id, oracle_name, value }) => {
-            cmd_oracle_update(&system_id, &oracle_name, value, json);
+        Some(Commands::OracleUpdate { system_id, oracle_name, value, source, expect_root, dry_run, trace }) => {
+            cmd_oracle_update(&system_id, &oracle_name, value, source, expect_root, dry_run, trace, json)
         }
 
         Some(Commands::Mint { address, amount, asset }) => {
-            cmd_mint(&address, amount, &asset, json);
+            cmd_mint(&address, amount, &asset, json)
         }
 
         Some(Commands::State { subcommand }) => match subcommand {
@@ -136,8 +208,22 @@ fn main() {
         Some(Commands::Replay) => cmd_replay(json),
 
         Some(Commands::Config { subcommand }) => match subcommand {
-            ConfigCommands::SetSender { address } => cmd_config_set_sender(&address),
+            ConfigCommands::SetSender { signing_key } => cmd_config_set_sender(&signing_key),
+            ConfigCommands::SetStore { backend, endpoint, region, bucket, access_key, secret_key, segment_size } => {
+                let s3_args = match (endpoint, region, bucket, access_key, secret_key) {
+                    (Some(endpoint), Some(region), Some(bucket), Some(access_key), Some(secret_key)) => {
+                        Some(S3StoreArgs { endpoint, region, bucket, access_key, secret_key, segment_size })
+                    }
+                    _ => None,
+                };
+                cmd_config_set_store(&backend, s3_args)
+            }
             ConfigCommands::Show => cmd_config_show(json),
         },
+    };
+
+    if let Err(e) = result {
+        Output::error(&e.to_string());
+        std::process::exit(1);
     }
 }
\ No newline at end of file
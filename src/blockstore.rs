@@ -0,0 +1,511 @@
+//! Abstraction over where the block log lives. `BlockLog`'s static methods
+//! (`append_to`, `read_all_from`, ...) hardwire a single local
+//! newline-delimited-JSON file; this module extracts that surface into the
+//! `BlockStore` trait so callers can swap in other persistence without
+//! touching replay logic, mirroring how `backend::StateBackend` abstracts
+//! over `State` storage.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::block::Block;
+use crate::log::{BlockLog, LogError};
+use crate::state::State;
+
+#[derive(Error, Debug)]
+pub enum ObjectStoreError {
+    #[error("invalid object store endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("connection to object store failed: {0}")]
+    ConnectionFailed(String),
+
+    #[error("object store returned HTTP {status}: {body}")]
+    HttpError { status: u16, body: String },
+
+    #[error("malformed response from object store")]
+    MalformedResponse,
+}
+
+/// Abstraction over "where the block log lives". `FileBlockStore` is the
+/// existing local-file behavior; `ObjectBlockStore` persists to an
+/// S3-compatible bucket instead. Both give callers the same five
+/// operations `BlockLog`'s static functions already provide for the local
+/// file case.
+pub trait BlockStore {
+    fn append(&self, block: &Block) -> Result<(), LogError>;
+    fn read_all(&self) -> Result<Vec<Block>, LogError>;
+    fn latest(&self) -> Result<Option<Block>, LogError>;
+    fn init_if_empty(&self, network_name: &str) -> Result<Block, LogError>;
+
+    /// Replays every stored block through `Runtime::apply_tx`, checking
+    /// each block's recorded `state_root` along the way. See
+    /// `BlockLog::rebuild_state_at` for the equivalent local-file logic;
+    /// this default just sources the blocks from `read_all` instead of a
+    /// fixed path, so it works unmodified for any backend. Runs the same
+    /// `verify_chain` integrity pass `rebuild_state_at` does before
+    /// replaying, so a backend that's been tampered with or forked fails
+    /// here instead of silently replaying a broken chain.
+    fn rebuild_state(&self) -> Result<State, LogError> {
+        let blocks = self.read_all()?;
+        if blocks.is_empty() {
+            return Ok(State::new());
+        }
+        BlockLog::verify_block_sequence(blocks.iter().cloned().map(Ok))?;
+        BlockLog::replay_blocks(State::new(), blocks.into_iter().map(Ok))
+    }
+}
+
+/// The existing on-disk backend: one append-only newline-delimited-JSON
+/// file at a fixed path, via `BlockLog`'s `_at`/`_from` functions.
+pub struct FileBlockStore {
+    log_path: String,
+}
+
+impl FileBlockStore {
+    pub fn new(log_path: &str) -> Self {
+        FileBlockStore { log_path: log_path.to_string() }
+    }
+}
+
+impl BlockStore for FileBlockStore {
+    fn append(&self, block: &Block) -> Result<(), LogError> {
+        BlockLog::append_to(block, &self.log_path)
+    }
+
+    fn read_all(&self) -> Result<Vec<Block>, LogError> {
+        BlockLog::read_all_validated_from(&self.log_path)
+    }
+
+    fn latest(&self) -> Result<Option<Block>, LogError> {
+        BlockLog::latest_from(&self.log_path)
+    }
+
+    fn init_if_empty(&self, network_name: &str) -> Result<Block, LogError> {
+        BlockLog::init_if_empty_at(&self.log_path, network_name)
+    }
+}
+
+/// Which `BlockStore` a node is configured to persist through — the
+/// `config set-store` selector. Lives in `CliConfig` so the
+/// choice survives across invocations the same way `sender`/cache
+/// capacities do.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StoreBackendConfig {
+    /// The existing local-file log at `log_path`.
+    #[default]
+    File,
+    /// An S3-compatible bucket, via `ObjectBlockStore`/`HttpObjectStoreClient`.
+    S3(ObjectStoreConfig),
+}
+
+impl StoreBackendConfig {
+    /// Builds the `BlockStore` this config selects, reading/appending
+    /// `log_path` for the `File` variant.
+    pub fn build(&self, log_path: &str) -> Box<dyn BlockStore> {
+        match self {
+            StoreBackendConfig::File => Box::new(FileBlockStore::new(log_path)),
+            StoreBackendConfig::S3(config) => {
+                let client = HttpObjectStoreClient::new(config.clone());
+                Box::new(ObjectBlockStore::new(config.clone(), client))
+            }
+        }
+    }
+}
+
+/// What `ObjectBlockStore` needs to talk to an S3-compatible bucket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectStoreConfig {
+    /// Plain-HTTP endpoint, e.g. `http://localhost:9000` for a local MinIO.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Max blocks held in the open segment before it's finalized and a new
+    /// one is rolled.
+    pub segment_size: u64,
+}
+
+/// The narrow get/put/list surface `ObjectBlockStore` needs, kept separate
+/// from the actual HTTP plumbing (`HttpObjectStoreClient`) so tests can
+/// swap in an in-memory double instead of hitting a real bucket.
+pub trait ObjectStoreClient {
+    fn put_object(&self, key: &str, bytes: &[u8]) -> Result<(), ObjectStoreError>;
+    fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>, ObjectStoreError>;
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, ObjectStoreError>;
+}
+
+const HEAD_KEY: &str = "HEAD";
+const OPEN_SEGMENT_KEY: &str = "segments/open.ndjson";
+
+/// The small mutable object recording the chain tip, so `latest` is a
+/// single GET instead of a scan over every segment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ObjectStoreHead {
+    latest_block: Block,
+    /// Block number the currently-open segment starts at.
+    open_segment_start: u64,
+}
+
+/// Blocks batched into fixed-size segment objects
+/// (`segments/<start>-<end>.ndjson`) plus a `HEAD` object for the tip, on
+/// an S3-compatible bucket. `append` rewrites the still-open segment (or
+/// finalizes it and opens a fresh one) rather than relying on a true
+/// append, since object stores don't offer one.
+pub struct ObjectBlockStore<C: ObjectStoreClient> {
+    config: ObjectStoreConfig,
+    client: C,
+}
+
+impl<C: ObjectStoreClient> ObjectBlockStore<C> {
+    pub fn new(config: ObjectStoreConfig, client: C) -> Self {
+        ObjectBlockStore { config, client }
+    }
+
+    fn read_head(&self) -> Result<Option<ObjectStoreHead>, LogError> {
+        match self.client.get_object(HEAD_KEY)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn write_head(&self, head: &ObjectStoreHead) -> Result<(), LogError> {
+        let bytes = serde_json::to_vec(head)?;
+        Ok(self.client.put_object(HEAD_KEY, &bytes)?)
+    }
+
+    fn read_segment_blocks(&self, key: &str) -> Result<Vec<Block>, LogError> {
+        match self.client.get_object(key)? {
+            Some(bytes) => parse_ndjson_blocks(&bytes),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn finalized_segment_key(start: u64, end: u64) -> String {
+        format!("segments/{}-{}.ndjson", start, end)
+    }
+}
+
+fn parse_ndjson_blocks(bytes: &[u8]) -> Result<Vec<Block>, LogError> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut blocks = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        blocks.push(serde_json::from_str(line)?);
+    }
+    Ok(blocks)
+}
+
+fn encode_ndjson_blocks(blocks: &[Block]) -> Result<Vec<u8>, LogError> {
+    let mut bytes = Vec::new();
+    for block in blocks {
+        bytes.extend(serde_json::to_vec(block)?);
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+/// Numeric start of a `segments/<start>-<end>.ndjson` key, for sort order.
+/// Keys that don't match the pattern sort first (start 0) — in practice
+/// the only non-matching key under `segments/` is the open segment, which
+/// callers filter out before sorting.
+fn segment_range_start(key: &str) -> u64 {
+    key.rsplit('/')
+        .next()
+        .unwrap_or(key)
+        .trim_end_matches(".ndjson")
+        .split('-')
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+impl<C: ObjectStoreClient> BlockStore for ObjectBlockStore<C> {
+    fn append(&self, block: &Block) -> Result<(), LogError> {
+        let mut open_blocks = self.read_segment_blocks(OPEN_SEGMENT_KEY)?;
+        let open_segment_start = self
+            .read_head()?
+            .map(|head| head.open_segment_start)
+            .unwrap_or(block.number);
+
+        open_blocks.push(block.clone());
+
+        if open_blocks.len() as u64 >= self.config.segment_size {
+            let finalized_key = Self::finalized_segment_key(open_segment_start, block.number);
+            let bytes = encode_ndjson_blocks(&open_blocks)?;
+            self.client.put_object(&finalized_key, &bytes)?;
+            self.client.put_object(OPEN_SEGMENT_KEY, &[])?;
+
+            self.write_head(&ObjectStoreHead {
+                latest_block: block.clone(),
+                open_segment_start: block.number + 1,
+            })
+        } else {
+            let bytes = encode_ndjson_blocks(&open_blocks)?;
+            self.client.put_object(OPEN_SEGMENT_KEY, &bytes)?;
+
+            self.write_head(&ObjectStoreHead {
+                latest_block: block.clone(),
+                open_segment_start,
+            })
+        }
+    }
+
+    fn read_all(&self) -> Result<Vec<Block>, LogError> {
+        let mut segment_keys: Vec<String> = self
+            .client
+            .list_objects("segments/")?
+            .into_iter()
+            .filter(|key| key != OPEN_SEGMENT_KEY)
+            .collect();
+        segment_keys.sort_by_key(|key| segment_range_start(key));
+
+        let mut blocks = Vec::new();
+        for key in &segment_keys {
+            blocks.extend(self.read_segment_blocks(key)?);
+        }
+        blocks.extend(self.read_segment_blocks(OPEN_SEGMENT_KEY)?);
+
+        Ok(blocks)
+    }
+
+    fn latest(&self) -> Result<Option<Block>, LogError> {
+        Ok(self.read_head()?.map(|head| head.latest_block))
+    }
+
+    fn init_if_empty(&self, network_name: &str) -> Result<Block, LogError> {
+        if let Some(latest) = self.latest()? {
+            return Ok(latest);
+        }
+
+        let genesis = Block::genesis(network_name);
+        self.append(&genesis)?;
+        Ok(genesis)
+    }
+}
+
+/// Real `ObjectStoreClient` over plain HTTP, path-style requests
+/// (`{endpoint}/{bucket}/{key}`), in the same dependency-free spirit as
+/// `rpc::call`.
+///
+/// Note: this does not implement real AWS SigV4 — this codebase carries no
+/// HMAC-SHA256 dependency, only the `keccak256` already used for hashing
+/// elsewhere (see `hash::keccak256`), so requests are authenticated with a
+/// simplified keccak256-keyed scheme instead of SigV4. An endpoint that
+/// requires real SigV4 needs a reverse proxy in front that rewrites it, or
+/// this client extended with a sha2/hmac dependency.
+pub struct HttpObjectStoreClient {
+    config: ObjectStoreConfig,
+}
+
+impl HttpObjectStoreClient {
+    pub fn new(config: ObjectStoreConfig) -> Self {
+        HttpObjectStoreClient { config }
+    }
+
+    fn object_path(&self, key: &str) -> String {
+        format!("/{}/{}", self.config.bucket, key)
+    }
+
+    fn authorization(&self, method: &str, path: &str) -> String {
+        let material = format!(
+            "{}:{}:{}:{}:{}",
+            self.config.access_key, self.config.secret_key, self.config.region, method, path
+        );
+        let signature = crate::hash::keccak256(material.as_bytes());
+        format!("FVL-OBJSTORE-HMAC {}:{}", self.config.access_key, hex::encode(signature))
+    }
+
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> Result<(u16, Vec<u8>), ObjectStoreError> {
+        let endpoint = crate::rpc::parse_url(&self.config.endpoint)
+            .map_err(|e| ObjectStoreError::InvalidEndpoint(e.to_string()))?;
+        let full_path = format!("{}{}", endpoint.path.trim_end_matches('/'), path);
+
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nAuthorization: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            method,
+            full_path,
+            endpoint.host,
+            self.authorization(method, &full_path),
+            body.len(),
+        );
+
+        let mut stream = TcpStream::connect((endpoint.host.as_str(), endpoint.port))
+            .map_err(|e| ObjectStoreError::ConnectionFailed(e.to_string()))?;
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| ObjectStoreError::ConnectionFailed(e.to_string()))?;
+        stream
+            .write_all(body)
+            .map_err(|e| ObjectStoreError::ConnectionFailed(e.to_string()))?;
+
+        let mut raw = Vec::new();
+        stream
+            .read_to_end(&mut raw)
+            .map_err(|e| ObjectStoreError::ConnectionFailed(e.to_string()))?;
+
+        let header_end = raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or(ObjectStoreError::MalformedResponse)?;
+        let header_text = String::from_utf8_lossy(&raw[..header_end]);
+        let status = header_text
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or(ObjectStoreError::MalformedResponse)?;
+
+        Ok((status, raw[header_end + 4..].to_vec()))
+    }
+}
+
+impl ObjectStoreClient for HttpObjectStoreClient {
+    fn put_object(&self, key: &str, bytes: &[u8]) -> Result<(), ObjectStoreError> {
+        let (status, body) = self.request("PUT", &self.object_path(key), bytes)?;
+        if !(200..300).contains(&status) {
+            return Err(ObjectStoreError::HttpError { status, body: String::from_utf8_lossy(&body).into_owned() });
+        }
+        Ok(())
+    }
+
+    fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>, ObjectStoreError> {
+        let (status, body) = self.request("GET", &self.object_path(key), &[])?;
+        if status == 404 {
+            return Ok(None);
+        }
+        if !(200..300).contains(&status) {
+            return Err(ObjectStoreError::HttpError { status, body: String::from_utf8_lossy(&body).into_owned() });
+        }
+        Ok(Some(body))
+    }
+
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, ObjectStoreError> {
+        let path = format!("/{}?list-type=2&prefix={}", self.config.bucket, prefix);
+        let (status, body) = self.request("GET", &path, &[])?;
+        if !(200..300).contains(&status) {
+            return Err(ObjectStoreError::HttpError { status, body: String::from_utf8_lossy(&body).into_owned() });
+        }
+
+        // ListObjectsV2 XML: `<Key>...</Key>` per entry. Scanned by hand
+        // rather than pulling in an XML parser, matching this codebase's
+        // general avoidance of extra dependencies for one-off parsing.
+        let text = String::from_utf8_lossy(&body);
+        let mut keys = Vec::new();
+        let mut rest = text.as_ref();
+        while let Some(start) = rest.find("<Key>") {
+            rest = &rest[start + "<Key>".len()..];
+            let Some(end) = rest.find("</Key>") else { break };
+            keys.push(rest[..end].to_string());
+            rest = &rest[end + "</Key>".len()..];
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// In-memory `ObjectStoreClient` double, so `ObjectBlockStore`'s
+    /// segment/HEAD logic can be tested without a real bucket.
+    struct InMemoryObjectStoreClient {
+        objects: RefCell<HashMap<String, Vec<u8>>>,
+    }
+
+    impl InMemoryObjectStoreClient {
+        fn new() -> Self {
+            InMemoryObjectStoreClient { objects: RefCell::new(HashMap::new()) }
+        }
+    }
+
+    impl ObjectStoreClient for InMemoryObjectStoreClient {
+        fn put_object(&self, key: &str, bytes: &[u8]) -> Result<(), ObjectStoreError> {
+            self.objects.borrow_mut().insert(key.to_string(), bytes.to_vec());
+            Ok(())
+        }
+
+        fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>, ObjectStoreError> {
+            Ok(self.objects.borrow().get(key).cloned())
+        }
+
+        fn list_objects(&self, prefix: &str) -> Result<Vec<String>, ObjectStoreError> {
+            Ok(self.objects.borrow().keys().filter(|k| k.starts_with(prefix)).cloned().collect())
+        }
+    }
+
+    fn test_config() -> ObjectStoreConfig {
+        ObjectStoreConfig {
+            endpoint: "http://localhost:9000".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: "fvl-blocks".to_string(),
+            access_key: "test-access".to_string(),
+            secret_key: "test-secret".to_string(),
+            segment_size: 2,
+        }
+    }
+
+    #[test]
+    fn test_append_then_latest_round_trips() {
+        let store = ObjectBlockStore::new(test_config(), InMemoryObjectStoreClient::new());
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        store.append(&genesis).unwrap();
+
+        let latest = store.latest().unwrap().unwrap();
+        assert_eq!(latest.number, 0);
+    }
+
+    #[test]
+    fn test_append_rolls_segment_at_configured_size() {
+        let store = ObjectBlockStore::new(test_config(), InMemoryObjectStoreClient::new());
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        store.append(&genesis).unwrap();
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], "0xroot1".to_string());
+        store.append(&block1).unwrap();
+
+        // segment_size is 2, so blocks 0-1 should now be finalized and the
+        // open segment reset for block 2 onward.
+        let keys = store.client.list_objects("segments/").unwrap();
+        assert!(keys.contains(&"segments/0-1.ndjson".to_string()));
+
+        let block2 = Block::new(2, block1.hash.clone(), vec![], "0xroot2".to_string());
+        store.append(&block2).unwrap();
+
+        let all = store.read_all().unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all.iter().map(|b| b.number).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_init_if_empty_creates_genesis_once() {
+        let store = ObjectBlockStore::new(test_config(), InMemoryObjectStoreClient::new());
+
+        let first = store.init_if_empty("FVL_TESTNET").unwrap();
+        assert_eq!(first.number, 0);
+
+        let second = store.init_if_empty("FVL_TESTNET").unwrap();
+        assert_eq!(second.number, 0);
+        assert_eq!(store.read_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_state_matches_file_backed_store() {
+        let object_store = ObjectBlockStore::new(test_config(), InMemoryObjectStoreClient::new());
+        let genesis = Block::genesis("FVL_TESTNET");
+        object_store.append(&genesis).unwrap();
+
+        let state = object_store.rebuild_state().unwrap();
+        assert_eq!(state.state_root_hex(), State::new().state_root_hex());
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::hash::keccak256;
+
+/// One narrowed permission: the ability to do `ability` against `resource`
+/// (a system ID). A token's `att` is the set of these it's allowed to
+/// exercise or delegate onward.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attenuation {
+    pub resource: String,
+    pub ability: String,
+}
+
+/// A UCAN-style delegable capability grant. `iss` delegates (a subset of)
+/// its own `att` to `aud`, who can present the token directly or delegate a
+/// narrower grant onward by issuing a child token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityToken {
+    pub iss: String,
+    pub aud: String,
+    pub att: Vec<Attenuation>,
+    pub exp: u64,
+    pub nbf: u64,
+    /// Digests (see `digest_hex`) of the parent token(s) this one was
+    /// delegated from. Empty for a root token.
+    #[serde(default)]
+    pub prf: Vec<String>,
+    /// Signature over `canonical_bytes()` by `iss`. Hex-encoded.
+    pub sig: String,
+}
+
+impl CapabilityToken {
+    /// The bytes the signature covers: everything but `sig` itself.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        #[derive(Serialize)]
+        struct Unsigned<'a> {
+            iss: &'a str,
+            aud: &'a str,
+            att: &'a [Attenuation],
+            exp: u64,
+            nbf: u64,
+            prf: &'a [String],
+        }
+
+        serde_json::to_vec(&Unsigned {
+            iss: &self.iss,
+            aud: &self.aud,
+            att: &self.att,
+            exp: self.exp,
+            nbf: self.nbf,
+            prf: &self.prf,
+        })
+        .expect("Failed to serialize capability token")
+    }
+
+    /// Content-addressed digest used to link a child's `prf` to its parent.
+    pub fn digest_hex(&self) -> String {
+        format!("0x{}", hex::encode(keccak256(&self.canonical_bytes())))
+    }
+
+    /// Recovers the address that produced `sig` over `canonical_bytes()`
+    /// (the same Ethereum-style `ecrecover` scheme `Transaction` signs
+    /// with, see `signing::recover_address`) and requires it to equal
+    /// `iss` — a token is only as trustworthy as proof that its claimed
+    /// issuer actually signed it.
+    pub fn verify_signature(&self) -> bool {
+        let hash = keccak256(&self.canonical_bytes());
+        crate::signing::recover_address(&hash, &self.sig)
+            .map(|recovered| recovered == self.iss)
+            .unwrap_or(false)
+    }
+
+    /// Signs `self` with `keypair`, setting `iss` to the keypair's derived
+    /// address so the two can never diverge (mirrors `Transaction::sign`).
+    pub fn sign(mut self, keypair: &crate::signing::Keypair) -> CapabilityToken {
+        self.iss = keypair.address_hex();
+        let hash = keccak256(&self.canonical_bytes());
+        self.sig = crate::signing::sign_hash(&hash, keypair);
+        self
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.exp
+    }
+
+    pub fn is_not_yet_valid(&self, now: u64) -> bool {
+        now < self.nbf
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CapabilityError {
+    #[error("proof chain is empty")]
+    EmptyChain,
+
+    #[error("token from {0} has expired")]
+    Expired(String),
+
+    #[error("token from {0} is not yet valid")]
+    NotYetValid(String),
+
+    #[error("token from {0} has an invalid signature")]
+    InvalidSignature(String),
+
+    #[error("root token issuer {0} is not the system owner or an existing rights holder")]
+    UntrustedRoot(String),
+
+    #[error("token audience {aud} does not match the next issuer {next_iss}")]
+    BrokenChain { aud: String, next_iss: String },
+
+    #[error("token does not reference its parent in `prf`")]
+    MissingProof,
+
+    #[error("token attempts to widen its parent's attenuations")]
+    OverBroadAttenuation,
+
+    #[error("leaf token's audience {aud} does not match sender {sender}")]
+    AudienceMismatch { aud: String, sender: String },
+
+    #[error("leaf token does not grant {ability} on {resource}")]
+    InsufficientAbility { resource: String, ability: String },
+}
+
+/// `rights` is keyed address (or `"anyone"`) → abilities, the same shape
+/// `Runtime::check_rights` reads (see `runtime.rs`) — not ability →
+/// addresses. An address "holds rights" here if it has at least one
+/// ability granted, directly or via `"anyone"`.
+fn rights_grant_address(rights: &HashMap<String, Vec<String>>, address: &str) -> bool {
+    rights.get(address).map(|abilities| !abilities.is_empty()).unwrap_or(false)
+        || rights.get("anyone").map(|abilities| !abilities.is_empty()).unwrap_or(false)
+}
+
+fn is_attenuation_subset(child: &[Attenuation], parent: &[Attenuation]) -> bool {
+    child.iter().all(|c| parent.iter().any(|p| p.resource == c.resource && p.ability == c.ability))
+}
+
+/// Walks a delegation chain root-to-leaf, checking expiry, signatures,
+/// issuer/audience linkage, proof references, and monotonically narrowing
+/// attenuations, then confirms the leaf grants `ability` on `resource` to
+/// `sender`.
+pub fn verify_chain(
+    chain: &[CapabilityToken],
+    system_owner: &str,
+    static_rights: &HashMap<String, Vec<String>>,
+    sender: &str,
+    resource: &str,
+    ability: &str,
+    now: u64,
+) -> Result<(), CapabilityError> {
+    let root = chain.first().ok_or(CapabilityError::EmptyChain)?;
+
+    if root.iss != system_owner && !rights_grant_address(static_rights, &root.iss) {
+        return Err(CapabilityError::UntrustedRoot(root.iss.clone()));
+    }
+
+    for (i, token) in chain.iter().enumerate() {
+        if token.is_expired(now) {
+            return Err(CapabilityError::Expired(token.iss.clone()));
+        }
+        if token.is_not_yet_valid(now) {
+            return Err(CapabilityError::NotYetValid(token.iss.clone()));
+        }
+        if !token.verify_signature() {
+            return Err(CapabilityError::InvalidSignature(token.iss.clone()));
+        }
+
+        if i > 0 {
+            let parent = &chain[i - 1];
+            if parent.aud != token.iss {
+                return Err(CapabilityError::BrokenChain {
+                    aud: parent.aud.clone(),
+                    next_iss: token.iss.clone(),
+                });
+            }
+            if !token.prf.contains(&parent.digest_hex()) {
+                return Err(CapabilityError::MissingProof);
+            }
+            if !is_attenuation_subset(&token.att, &parent.att) {
+                return Err(CapabilityError::OverBroadAttenuation);
+            }
+        }
+    }
+
+    let leaf = chain.last().ok_or(CapabilityError::EmptyChain)?;
+    if leaf.aud != sender {
+        return Err(CapabilityError::AudienceMismatch {
+            aud: leaf.aud.clone(),
+            sender: sender.to_string(),
+        });
+    }
+    if !leaf.att.iter().any(|a| a.resource == resource && a.ability == ability) {
+        return Err(CapabilityError::InsufficientAbility {
+            resource: resource.to_string(),
+            ability: ability.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::Keypair;
+
+    const BOT: &str = "0x3333333333333333333333333333333333333333";
+    const SYSTEM_ID: &str = "0xsystem";
+
+    /// Builds an unsigned token; callers finish it with `.sign(keypair)`,
+    /// which also sets `iss` to the signer's derived address.
+    fn unsigned(aud: &str, ability: &str, exp: u64, nbf: u64, prf: Vec<String>) -> CapabilityToken {
+        CapabilityToken {
+            iss: String::new(),
+            aud: aud.to_string(),
+            att: vec![Attenuation { resource: SYSTEM_ID.to_string(), ability: ability.to_string() }],
+            exp,
+            nbf,
+            prf,
+            sig: String::new(),
+        }
+    }
+
+    fn root_token(owner: &Keypair, aud: &str) -> CapabilityToken {
+        unsigned(aud, "swap", 2_000_000_000, 0, vec![]).sign(owner)
+    }
+
+    fn delegate_to_bot(root: &CapabilityToken, market_maker: &Keypair) -> CapabilityToken {
+        unsigned(BOT, "swap", 2_000_000_000, 0, vec![root.digest_hex()]).sign(market_maker)
+    }
+
+    #[test]
+    fn test_single_token_chain_grants_ability_to_audience() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_delegated_chain_grants_narrower_ability_to_bot() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        let leaf = delegate_to_bot(&root, &market_maker);
+        let rights = HashMap::new();
+        let result = verify_chain(&[root, leaf], &owner.address_hex(), &rights, BOT, SYSTEM_ID, "swap", 1_000);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_rejects_untrusted_root_issuer() {
+        let owner = Keypair::generate();
+        let attacker = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&attacker, &market_maker.address_hex());
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert_eq!(result, Err(CapabilityError::UntrustedRoot(attacker.address_hex())));
+    }
+
+    #[test]
+    fn test_rejects_expired_token() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = unsigned(&market_maker.address_hex(), "swap", 500, 0, vec![]).sign(&owner);
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert!(matches!(result, Err(CapabilityError::Expired(_))));
+    }
+
+    #[test]
+    fn test_rejects_broken_audience_chain() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let impostor = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        // Validly signed, but by someone the root never delegated to.
+        let leaf = unsigned(BOT, "swap", 2_000_000_000, 0, vec![root.digest_hex()]).sign(&impostor);
+        let rights = HashMap::new();
+        let result = verify_chain(&[root, leaf], &owner.address_hex(), &rights, BOT, SYSTEM_ID, "swap", 1_000);
+        assert!(matches!(result, Err(CapabilityError::BrokenChain { .. })));
+    }
+
+    #[test]
+    fn test_rejects_over_broad_attenuation() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        let mut leaf = unsigned(BOT, "swap", 2_000_000_000, 0, vec![root.digest_hex()]);
+        leaf.att.push(Attenuation { resource: SYSTEM_ID.to_string(), ability: "add_liquidity".to_string() });
+        let leaf = leaf.sign(&market_maker);
+        let rights = HashMap::new();
+        let result = verify_chain(&[root, leaf], &owner.address_hex(), &rights, BOT, SYSTEM_ID, "swap", 1_000);
+        assert_eq!(result, Err(CapabilityError::OverBroadAttenuation));
+    }
+
+    #[test]
+    fn test_rejects_leaf_not_matching_sender() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, BOT, SYSTEM_ID, "swap", 1_000);
+        assert!(matches!(result, Err(CapabilityError::AudienceMismatch { .. })));
+    }
+
+    #[test]
+    fn test_rejects_missing_requested_ability() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&owner, &market_maker.address_hex());
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "add_liquidity", 1_000);
+        assert!(matches!(result, Err(CapabilityError::InsufficientAbility { .. })));
+    }
+
+    #[test]
+    fn test_root_trusted_via_static_rights_grant() {
+        let owner = Keypair::generate();
+        let rights_holder = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let root = root_token(&rights_holder, &market_maker.address_hex());
+        let mut rights = HashMap::new();
+        rights.insert(rights_holder.address_hex(), vec!["swap".to_string()]);
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_rejects_forged_signature() {
+        let owner = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let mut root = root_token(&owner, &market_maker.address_hex());
+        root.sig = format!("0x{}", hex::encode([0u8; 65]));
+        let rights = HashMap::new();
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert!(matches!(result, Err(CapabilityError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn test_rejects_issuer_not_matching_signer() {
+        let owner = Keypair::generate();
+        let impostor = Keypair::generate();
+        let market_maker = Keypair::generate();
+        let mut root = root_token(&owner, &market_maker.address_hex());
+        // Claims to be `impostor` (and is trusted as one via static rights),
+        // but the signature still recovers to `owner`.
+        root.iss = impostor.address_hex();
+        let mut rights = HashMap::new();
+        rights.insert(impostor.address_hex(), vec!["swap".to_string()]);
+        let result = verify_chain(&[root], &owner.address_hex(), &rights, &market_maker.address_hex(), SYSTEM_ID, "swap", 1_000);
+        assert!(matches!(result, Err(CapabilityError::InvalidSignature(_))));
+    }
+}
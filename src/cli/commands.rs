@@ -1,24 +1,82 @@
-use crate::cli::config::CliConfig;
+use crate::blockstore::{BlockStore, ObjectStoreConfig, StoreBackendConfig};
+use crate::cli::config::{CliConfig, ConfigError};
 use crate::cli::output::Output;
-use crate::log::BlockLog;
-use crate::parser::Parser;
-use crate::sequencer::sequence_tx;
-use crate::store::Store;
+use crate::log::{BlockLog, LogError, LOG_PATH};
+use crate::parser::{Parser, ParseError};
+use crate::runtime::Runtime;
+use crate::sequencer::{sequence_tx, SequencerError};
+use crate::state::State;
+use crate::store::{Store, StoreError};
 use crate::transaction::{Transaction, TransactionPayload, TransactionAsset, InteractMode};
-use crate::hash::{compute_system_id, system_id_to_hex};
-
-/// Deploy a YAML system file
-pub fn cmd_deploy(yaml_path: &str, as_json: bool) {
-    // Parse and validate
-    let system = match Parser::parse_file(yaml_path) {
-        Ok(s) => s,
-        Err(e) => {
-            Output::error(&format!("Parse error: {}", e));
-            return;
+use crate::hash::{compute_system_id, system_id_to_hex, HashError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Parse error: {0}")]
+    Parse(#[from] ParseError),
+
+    #[error("Hash error: {0}")]
+    Hash(#[from] HashError),
+
+    #[error("State error: {0}")]
+    Store(#[from] StoreError),
+
+    #[error("Log error: {0}")]
+    Log(#[from] LogError),
+
+    #[error("Sequencer error: {0}")]
+    Sequencer(#[from] SequencerError),
+
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("Invalid signing key: {0}")]
+    InvalidSigningKey(crate::signing::SigningError),
+
+    #[error("No signing key configured. Run: fvl config set-sender <hex-seed>")]
+    NoSigningKey,
+
+    #[error("System not found: {0}")]
+    SystemNotFound(String),
+
+    #[error("--action required for {0} mode")]
+    ActionRequired(String),
+
+    #[error("Unknown mode: {0}. Use: evaluate, trigger, both")]
+    UnknownMode(String),
+
+    #[error("Unknown store backend: {0}. Use: file, s3")]
+    UnknownStoreBackend(String),
+
+    #[error("--endpoint/--region/--bucket/--access-key/--secret-key required for the s3 backend")]
+    MissingS3Args,
+}
+
+/// Deploy a YAML system file. If `env_name` is given, the named entry
+/// under the file's top-level `environments` map is deep-merged onto the
+/// base document (see `Parser::parse_yaml_env`) and the *merged* YAML is
+/// what gets deployed/stored, since `system_registry`'s replay path only
+/// ever re-parses a plain `Parser::parse_yaml` document.
+pub fn cmd_deploy(yaml_path: &str, env_name: Option<&str>, expect_root: Option<String>, as_json: bool) -> Result<(), CommandError> {
+    let content = std::fs::read_to_string(yaml_path)?;
+
+    let (system, yaml) = match env_name {
+        Some(env_name) => {
+            let system = Parser::parse_yaml_env(&content, env_name)?;
+            let yaml = serde_yaml::to_string(&system).map_err(ParseError::YamlError)?;
+            (system, yaml)
         }
+        None => (Parser::parse_yaml(&content)?, content),
     };
 
-    let system_id_hex = system_id_to_hex(&compute_system_id(&system));
+    let system_id_hex = system_id_to_hex(&compute_system_id(&system)?);
 
     if !as_json {
         Output::header("Deploying System");
@@ -26,26 +84,19 @@ pub fn cmd_deploy(yaml_path: &str, as_json: bool) {
         Output::info("System ID", &system_id_hex);
     }
 
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+    let state = Store::load_verified_default()?;
 
     // Check if already deployed
     if state.systems.contains_key(&system_id_hex) {
         Output::warning(&format!("System {} already deployed", &system_id_hex[..16]));
         Output::info("System ID", &system_id_hex);
-        return;
+        return Ok(());
     }
 
-    // Initialize log if needed
-    if let Err(e) = BlockLog::init_if_empty() {
-        Output::error(&format!("Log error: {}", e));
-        return;
-    }
+    BlockLog::init_if_empty()?;
 
     let config = CliConfig::load_or_default();
-    let yaml = std::fs::read_to_string(yaml_path).unwrap();
+    let keypair = require_keypair(&config)?;
 
     let tx = Transaction {
         sender: config.sender.clone(),
@@ -54,21 +105,23 @@ pub fn cmd_deploy(yaml_path: &str, as_json: bool) {
             system_id: system_id_hex.clone(),
             yaml: Some(yaml),
         },
-    };
+        chain_id: crate::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: expect_root,
+        signature: String::new(),
+    }
+    .sign(&keypair);
 
-    match sequence_tx(tx, &state) {
-        Ok((result,_new_state )) => {
-            Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
-            if result.tx_result.success {
-                Output::info("System ID", &system_id_hex);
-            }
-        }
-        Err(e) => Output::error(&format!("Sequencer error: {}", e)),
+    let (result, _new_state) = sequence_tx(tx, &state)?;
+    Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
+    if result.tx_result.success {
+        Output::info("System ID", &system_id_hex);
     }
+    Ok(())
 }
 
 /// Transfer assets between addresses
-pub fn cmd_transfer(from: &str, to: &str, amount: u128, asset: &str, as_json: bool) {
+pub fn cmd_transfer(from: &str, to: &str, amount: u128, asset: &str, expect_root: Option<String>, dry_run: bool, as_json: bool) -> Result<(), CommandError> {
     if !as_json {
         Output::header("Transfer");
         Output::info("From", from);
@@ -77,17 +130,11 @@ pub fn cmd_transfer(from: &str, to: &str, amount: u128, asset: &str, as_json: bo
         Output::info("Asset", asset);
     }
 
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
-
-    if let Err(e) = BlockLog::init_if_empty() {
-        Output::error(&format!("Log error: {}", e));
-        return;
-    }
+    let state = Store::load_verified_default()?;
+    BlockLog::init_if_empty()?;
 
     let config = CliConfig::load_or_default();
+    let keypair = require_keypair(&config)?;
 
     let asset_type = parse_asset(asset);
 
@@ -100,71 +147,81 @@ pub fn cmd_transfer(from: &str, to: &str, amount: u128, asset: &str, as_json: bo
             asset_type,
             amount,
         },
-    };
+        chain_id: crate::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: expect_root,
+        signature: String::new(),
+    }
+    .sign(&keypair);
+
+    if dry_run {
+        return dry_run_preview(tx, &state, as_json);
+    }
 
-    match sequence_tx(tx, &state) {
-        Ok((result, new_state)) => {
-            Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
-            if result.tx_result.success && !as_json {
-                let new_from = new_state.get_balance(from, &crate::types::AssetType::Eth);
-                let new_to = new_state.get_balance(to, &crate::types::AssetType::Eth);
-                Output::info("New From Balance", &new_from.to_string());
-                Output::info("New To Balance", &new_to.to_string());
-            }
+    let (result, new_state) = sequence_tx(tx, &state)?;
+    Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
+    if result.tx_result.success && !as_json {
+        let new_from = new_state.get_balance(from, &crate::types::AssetType::Eth);
+        let new_to = new_state.get_balance(to, &crate::types::AssetType::Eth);
+        Output::info("New From Balance", &new_from.to_string());
+        Output::info("New To Balance", &new_to.to_string());
+    }
+    Ok(())
+}
+
+/// Simulates `tx` against a clone of `state` without appending a block,
+/// printing the resulting state delta (see `Output::state_diff`) instead of
+/// a block/hash confirmation. Backs the `--dry-run` flag on
+/// `transfer`/`interact`/`oracle-update`.
+fn dry_run_preview(tx: Transaction, state: &State, as_json: bool) -> Result<(), CommandError> {
+    let next_block = BlockLog::latest()?.map(|b| b.number + 1).unwrap_or(1);
+    let (new_state, tx_result) = Runtime::apply_tx(state, tx, next_block);
+
+    if !as_json {
+        if tx_result.success {
+            Output::success("Dry run: transaction would succeed");
+        } else {
+            Output::error(&format!(
+                "Dry run: transaction would fail: {}",
+                tx_result.error.as_ref().map(|e| e.to_string()).unwrap_or_default()
+            ));
         }
-        Err(e) => Output::error(&format!("Sequencer error: {}", e)),
     }
+
+    Output::state_diff(state, &new_state, as_json);
+    Ok(())
 }
 
 /// Interact with a deployed system
-pub fn cmd_interact(system_id: &str, mode: &str, action: Option<&str>, as_json: bool) {
+pub fn cmd_interact(system_id: &str, mode: &str, action: Option<&str>, expect_root: Option<String>, dry_run: bool, trace: bool, as_json: bool) -> Result<(), CommandError> {
     if !as_json {
         Output::header("Interact");
         Output::info("System ID", system_id);
         Output::info("Mode", mode);
     }
 
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+    let state = Store::load_verified_default()?;
 
     if !state.systems.contains_key(system_id) {
-        Output::error(&format!("System not found: {}", system_id));
-        return;
+        return Err(CommandError::SystemNotFound(system_id.to_string()));
     }
 
-    if let Err(e) = BlockLog::init_if_empty() {
-        Output::error(&format!("Log error: {}", e));
-        return;
-    }
+    BlockLog::init_if_empty()?;
 
     let config = CliConfig::load_or_default();
+    let keypair = require_keypair(&config)?;
 
     let interact_mode = match mode {
         "evaluate" => InteractMode::EvaluateConditions,
-        "trigger" => {
-            match action {
-                Some(a) => InteractMode::TriggerAction { action: a.to_string() },
-                None => {
-                    Output::error("--action required for trigger mode");
-                    return;
-                }
-            }
-        }
-        "both" => {
-            match action {
-                Some(a) => InteractMode::Both { action: a.to_string() },
-                None => {
-                    Output::error("--action required for both mode");
-                    return;
-                }
-            }
-        }
-        _ => {
-            Output::error(&format!("Unknown mode: {}. Use: evaluate, trigger, both", mode));
-            return;
-        }
+        "trigger" => match action {
+            Some(a) => InteractMode::TriggerAction { action: a.to_string() },
+            None => return Err(CommandError::ActionRequired("trigger".to_string())),
+        },
+        "both" => match action {
+            Some(a) => InteractMode::Both { action: a.to_string() },
+            None => return Err(CommandError::ActionRequired("both".to_string())),
+        },
+        _ => return Err(CommandError::UnknownMode(mode.to_string())),
     };
 
     let tx = Transaction {
@@ -174,41 +231,44 @@ pub fn cmd_interact(system_id: &str, mode: &str, action: Option<&str>, as_json:
             system_id: system_id.to_string(),
             mode: interact_mode,
         },
-    };
+        chain_id: crate::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: expect_root,
+        signature: String::new(),
+    }
+    .sign(&keypair);
 
-    match sequence_tx(tx, &state) {
-        Ok((result, _)) => {
-            Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
-        }
-        Err(e) => Output::error(&format!("Sequencer error: {}", e)),
+    if dry_run {
+        return dry_run_preview(tx, &state, as_json);
     }
+
+    let (result, _) = sequence_tx(tx, &state)?;
+    Output::tx_result_traced(&result.tx_result, result.block.number, &result.block.hash, as_json, trace);
+    Ok(())
 }
 
 /// Update an oracle value
-pub fn cmd_oracle_update(system_id: &str, oracle_name: &str, value: u128, as_json: bool) {
+pub fn cmd_oracle_update(system_id: &str, oracle_name: &str, value: u128, source: Option<String>, expect_root: Option<String>, dry_run: bool, trace: bool, as_json: bool) -> Result<(), CommandError> {
     if !as_json {
         Output::header("Oracle Update");
         Output::info("System ID", system_id);
         Output::info("Oracle", oracle_name);
         Output::info("Value", &value.to_string());
+        if let Some(source) = &source {
+            Output::info("Source", source);
+        }
     }
 
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+    let state = Store::load_verified_default()?;
 
     if !state.systems.contains_key(system_id) {
-        Output::error(&format!("System not found: {}", system_id));
-        return;
+        return Err(CommandError::SystemNotFound(system_id.to_string()));
     }
 
-    if let Err(e) = BlockLog::init_if_empty() {
-        Output::error(&format!("Log error: {}", e));
-        return;
-    }
+    BlockLog::init_if_empty()?;
 
     let config = CliConfig::load_or_default();
+    let keypair = require_keypair(&config)?;
 
     let tx = Transaction {
         sender: config.sender.clone(),
@@ -217,45 +277,47 @@ pub fn cmd_oracle_update(system_id: &str, oracle_name: &str, value: u128, as_jso
             system_id: system_id.to_string(),
             oracle_name: oracle_name.to_string(),
             value,
+            source,
         },
-    };
+        chain_id: crate::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: expect_root,
+        signature: String::new(),
+    }
+    .sign(&keypair);
 
-    match sequence_tx(tx, &state) {
-        Ok((result, _)) => {
-            Output::tx_result(&result.tx_result, result.block.number, &result.block.hash, as_json);
-        }
-        Err(e) => Output::error(&format!("Sequencer error: {}", e)),
+    if dry_run {
+        return dry_run_preview(tx, &state, as_json);
     }
+
+    let (result, _) = sequence_tx(tx, &state)?;
+    Output::tx_result_traced(&result.tx_result, result.block.number, &result.block.hash, as_json, trace);
+    Ok(())
 }
 
 /// Print full state
-pub fn cmd_state(as_json: bool) {
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+pub fn cmd_state(as_json: bool) -> Result<(), CommandError> {
+    let state = Store::load_verified_default()?;
     Output::state(&state, as_json);
+    Ok(())
 }
 
 /// Print a specific system
-pub fn cmd_state_system(system_id: &str, as_json: bool) {
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+pub fn cmd_state_system(system_id: &str, as_json: bool) -> Result<(), CommandError> {
+    let state = Store::load_verified_default()?;
 
     match state.systems.get(system_id) {
-        Some(system_state) => Output::system(system_state, as_json),
-        None => Output::error(&format!("System not found: {}", system_id)),
+        Some(system_state) => {
+            Output::system(system_state, as_json);
+            Ok(())
+        }
+        None => Err(CommandError::SystemNotFound(system_id.to_string())),
     }
 }
 
 /// Print balances for an address
-pub fn cmd_state_balance(address: &str, as_json: bool) {
-    let state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+pub fn cmd_state_balance(address: &str, as_json: bool) -> Result<(), CommandError> {
+    let state = Store::load_verified_default()?;
 
     let balances: Vec<(String, u128)> = state.balances
         .iter()
@@ -264,69 +326,117 @@ pub fn cmd_state_balance(address: &str, as_json: bool) {
         .collect();
 
     Output::balance(address, balances, as_json);
+    Ok(())
 }
 
 /// Print block log
-pub fn cmd_blocks(as_json: bool) {
-    match BlockLog::read_all() {
-        Ok(blocks) => Output::blocks(&blocks, as_json),
-        Err(e) => Output::error(&format!("Log error: {}", e)),
-    }
+pub fn cmd_blocks(as_json: bool) -> Result<(), CommandError> {
+    let blocks = BlockLog::read_all()?;
+    Output::blocks(&blocks, as_json);
+    Ok(())
 }
 
-/// Rebuild state from log
-pub fn cmd_replay(as_json: bool) {
+/// Rebuild state from the configured `BlockStore` backend (local file by
+/// default, or whatever `fvl config set-store` last selected), detecting a
+/// corrupt block log or state store by recomputing the state root at each
+/// block and comparing it against the root the block claims (see
+/// `LogError::StateRootMismatch`).
+pub fn cmd_replay(as_json: bool) -> Result<(), CommandError> {
     if !as_json {
         Output::header("Replaying State from Log");
     }
 
-    match BlockLog::rebuild_state() {
-        Ok(state) => {
-            if !as_json {
-                Output::success("State rebuilt successfully");
-                Output::info("State Root", &state.state_root_hex());
-                Output::info("Systems", &state.systems.len().to_string());
-            } else {
-                println!("{}", serde_json::to_string_pretty(&state).unwrap());
-            }
-        }
-        Err(e) => Output::error(&format!("Replay error: {}", e)),
+    let config = CliConfig::load_or_default();
+    let store = config.store_backend.build(LOG_PATH);
+    let state = store.rebuild_state()?;
+    Store::save(&state)?;
+
+    if !as_json {
+        Output::success("State rebuilt successfully");
+        Output::info("State Root", &state.state_root_hex());
+        Output::info("Systems", &state.systems.len().to_string());
+    } else {
+        println!("{}", serde_json::to_string_pretty(&state)?);
     }
+    Ok(())
 }
 
-/// Set sender address
-pub fn cmd_config_set_sender(address: &str) {
-    // Validate address format
-    let re = regex::Regex::new(r"^0x[a-fA-F0-9]{40}$").unwrap();
-    if !re.is_match(address) {
-        Output::error(&format!("Invalid Ethereum address: {}", address));
-        return;
-    }
+/// Import a signing key (hex-encoded secp256k1 seed) and derive `sender`
+/// from it. `sender` can no longer be set directly: every transaction is
+/// signed, so the address must be backed by a real keypair.
+pub fn cmd_config_set_sender(signing_key_hex: &str) -> Result<(), CommandError> {
+    let keypair = crate::signing::Keypair::from_seed_hex(signing_key_hex)
+        .map_err(CommandError::InvalidSigningKey)?;
+
+    let mut config = CliConfig::load_or_default();
+    config.sender = keypair.address_hex();
+    config.signing_key = Some(keypair.seed_hex());
+    config.save()?;
+    Output::success(&format!("Sender set to {}", config.sender));
+    Ok(())
+}
 
-    let config = CliConfig { sender: address.to_string() };
-    match config.save() {
-        Ok(_) => {
-            Output::success(&format!("Sender set to {}", address));
+/// The bucket details `ConfigCommands::SetStore { backend: "s3", .. }` needs
+/// to build an `ObjectStoreConfig`; unused for the `file` backend.
+pub struct S3StoreArgs {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub segment_size: u64,
+}
+
+/// Select which `BlockStore` backend (`blockstore::StoreBackendConfig`)
+/// future commands persist the block log through. `s3` requires `s3_args`;
+/// `file` ignores it.
+pub fn cmd_config_set_store(backend: &str, s3_args: Option<S3StoreArgs>) -> Result<(), CommandError> {
+    let mut config = CliConfig::load_or_default();
+
+    config.store_backend = match backend {
+        "file" => StoreBackendConfig::File,
+        "s3" => {
+            let args = s3_args.ok_or(CommandError::MissingS3Args)?;
+            StoreBackendConfig::S3(ObjectStoreConfig {
+                endpoint: args.endpoint,
+                region: args.region,
+                bucket: args.bucket,
+                access_key: args.access_key,
+                secret_key: args.secret_key,
+                segment_size: args.segment_size,
+            })
         }
-        Err(e) => Output::error(&format!("Config error: {}", e)),
-    }
+        other => return Err(CommandError::UnknownStoreBackend(other.to_string())),
+    };
+
+    config.save()?;
+    Output::success(&format!("Store backend set to {}", backend));
+    Ok(())
 }
 
 /// Show current config
-pub fn cmd_config_show(as_json: bool) {
+pub fn cmd_config_show(as_json: bool) -> Result<(), CommandError> {
     let config = CliConfig::load_or_default();
 
     if as_json {
-        println!("{}", serde_json::to_string_pretty(&config).unwrap());
-        return;
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
     }
 
     Output::header("Config");
     Output::info("Sender", &config.sender);
+    Ok(())
 }
 
-/// Mint balance directly (for testing)
-pub fn cmd_mint(address: &str, amount: u128, asset: &str, as_json: bool) {
+/// Mint balance directly (for testing). Deliberately bypasses the block
+/// log entirely rather than appending a block for this mutation — which
+/// means it must read and write `state.json` with the plain, unverified
+/// `Store::load`/`Store::save` rather than `Store::load_verified_default`:
+/// that verified path errors out whenever `state.json`'s root has drifted
+/// from the latest block's recorded root, and every `mint` is exactly
+/// that kind of out-of-band drift, so using it here would make `mint`
+/// brick every subsequent command that does read state verified.
+pub fn cmd_mint(address: &str, amount: u128, asset: &str, as_json: bool) -> Result<(), CommandError> {
     if !as_json {
         Output::header("Minting Balance");
         Output::info("Address", address);
@@ -334,29 +444,30 @@ pub fn cmd_mint(address: &str, amount: u128, asset: &str, as_json: bool) {
         Output::info("Asset", asset);
     }
 
-    let mut state = match Store::load() {
-        Ok(s) => s,
-        Err(e) => { Output::error(&format!("State error: {}", e)); return; }
-    };
+    let mut state = Store::load()?;
 
     let asset_type = parse_asset_to_type(asset);
     state.set_balance(address, &asset_type, amount);
 
-    match Store::save(&state) {
-        Ok(_) => {
-            if as_json {
-                println!("{}", serde_json::json!({
-                    "success": true,
-                    "address": address,
-                    "amount": amount.to_string(),
-                    "asset": asset
-                }));
-            } else {
-                Output::success(&format!("Minted {} {} to {}", amount, asset, address));
-            }
-        }
-        Err(e) => Output::error(&format!("Store error: {}", e)),
+    Store::save(&state)?;
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+            "success": true,
+            "address": address,
+            "amount": amount.to_string(),
+            "asset": asset
+        }))?);
+    } else {
+        Output::success(&format!("Minted {} {} to {}", amount, asset, address));
     }
+    Ok(())
+}
+
+/// Loads the signing keypair backing `config.sender`, erroring if none has
+/// been generated or imported yet.
+fn require_keypair(config: &CliConfig) -> Result<crate::signing::Keypair, CommandError> {
+    config.keypair().ok_or(CommandError::NoSigningKey)
 }
 
 /// Parse asset string to AssetType (for state operations)
@@ -396,4 +507,4 @@ pub fn parse_asset(asset: &str) -> TransactionAsset {
 
     // Default to ETH
     TransactionAsset::Eth
-}
\ No newline at end of file
+}
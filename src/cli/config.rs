@@ -3,16 +3,47 @@ use std::fs;
 use std::path::Path;
 use thiserror::Error;
 
+use crate::blockstore::StoreBackendConfig;
+
 const CONFIG_PATH: &str = "data/config.json";
+const DEFAULT_REGISTRY_CACHE_CAPACITY: usize = 256;
+const DEFAULT_ACCOUNT_CACHE_CAPACITY: usize = 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CliConfig {
     pub sender: String,
+
+    /// Hex-encoded secp256k1 signing seed backing `sender`. Generated once
+    /// and persisted on first run; `sender` always matches its derived address.
+    #[serde(default)]
+    pub signing_key: Option<String>,
+
+    /// Max number of system YAML entries kept in the registry LRU cache.
+    #[serde(default = "default_registry_cache_capacity")]
+    pub registry_cache_capacity: usize,
+
+    /// Max number of accounts (balances/nonces) kept in the state LRU cache.
+    #[serde(default = "default_account_cache_capacity")]
+    pub account_cache_capacity: usize,
+
+    /// Which `BlockStore` backend (`blockstore::BlockStore`) persists the
+    /// block log — the local file by default, or an S3-compatible bucket.
+    /// Set via `fvl config set-store`.
+    #[serde(default)]
+    pub store_backend: StoreBackendConfig,
+}
+
+fn default_registry_cache_capacity() -> usize {
+    DEFAULT_REGISTRY_CACHE_CAPACITY
+}
+
+fn default_account_cache_capacity() -> usize {
+    DEFAULT_ACCOUNT_CACHE_CAPACITY
 }
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
-    #[error("Config not found. Run: fvl config set-sender <address>")]
+    #[error("Config not found. Run: fvl config set-sender <hex-seed>")]
     NotFound,
 
     #[error("Failed to read config: {0}")]
@@ -39,26 +70,38 @@ impl CliConfig {
         Ok(config)
     }
 
-    /// Save config to data/config.json
+    /// Save config to data/config.json, crash-safely (write-temp-then-rename).
     pub fn save(&self) -> Result<(), ConfigError> {
-        if let Some(parent) = Path::new(CONFIG_PATH).parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| ConfigError::WriteError(e.to_string()))?;
-        }
-
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| ConfigError::WriteError(e.to_string()))?;
 
-        fs::write(CONFIG_PATH, json)
+        crate::fs_util::atomic_write(Path::new(CONFIG_PATH), json.as_bytes())
             .map_err(|e| ConfigError::WriteError(e.to_string()))?;
 
         Ok(())
     }
 
-    /// Load or return default Anvil sender
+    /// Load the config, generating and persisting a fresh signing
+    /// keypair on first run so `sender` is stable across invocations.
     pub fn load_or_default() -> Self {
-        Self::load().unwrap_or_else(|_| CliConfig {
-            sender: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
-        })
+        if let Ok(config) = Self::load() {
+            return config;
+        }
+
+        let keypair = crate::signing::Keypair::generate();
+        let config = CliConfig {
+            sender: keypair.address_hex(),
+            signing_key: Some(keypair.seed_hex()),
+            registry_cache_capacity: default_registry_cache_capacity(),
+            account_cache_capacity: default_account_cache_capacity(),
+            store_backend: StoreBackendConfig::default(),
+        };
+        let _ = config.save();
+        config
+    }
+
+    /// The keypair backing `sender`, if one has been generated or imported.
+    pub fn keypair(&self) -> Option<crate::signing::Keypair> {
+        crate::signing::Keypair::from_seed_hex(self.signing_key.as_deref()?).ok()
     }
 }
\ No newline at end of file
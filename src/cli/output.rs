@@ -1,7 +1,7 @@
 use colored::*;
 use prettytable::{Table, row};
 use serde_json::{json, Value};
-use crate::state::{State, SystemState};
+use crate::state::{State, StateChange, SystemState};
 use crate::block::Block;
 use crate::runtime::TxResult;
 
@@ -29,14 +29,33 @@ impl Output {
     }
 
     pub fn tx_result(result: &TxResult, block_number: u64, block_hash: &str, as_json: bool) {
+        Self::tx_result_traced(result, block_number, block_hash, as_json, false)
+    }
+
+    /// Like `tx_result`, but when `show_trace` is set also surfaces
+    /// `result.trace` (see `runtime::TraceEvent`) — a `trace` array in JSON
+    /// mode, one line per event otherwise. Opt-in since most callers don't
+    /// need to see every condition/action/mutation the transaction went
+    /// through.
+    pub fn tx_result_traced(
+        result: &TxResult,
+        block_number: u64,
+        block_hash: &str,
+        as_json: bool,
+        show_trace: bool,
+    ) {
         if as_json {
-            let json = json!({
+            let mut json = json!({
                 "success": result.success,
                 "block_number": block_number,
                 "block_hash": block_hash,
                 "state_root": result.state_root,
+                "fee_charged": result.fee_charged.to_string(),
                 "error": result.error.as_ref().map(|e| e.to_string())
             });
+            if show_trace {
+                json["trace"] = serde_json::to_value(&result.trace).unwrap();
+            }
             println!("{}", serde_json::to_string_pretty(&json).unwrap());
             return;
         }
@@ -53,6 +72,19 @@ impl Output {
         Output::info("Block", &block_number.to_string());
         Output::info("Block Hash", block_hash);
         Output::info("State Root", &result.state_root);
+        if result.fee_charged > 0 {
+            Output::info("Fee Charged", &result.fee_charged.to_string());
+        }
+
+        if show_trace {
+            println!("\n  {}", "Trace".bold().cyan());
+            if result.trace.is_empty() {
+                println!("    {}", "(no events)".dimmed());
+            }
+            for event in &result.trace {
+                println!("    {:?}", event);
+            }
+        }
     }
 
     pub fn state(state: &State, as_json: bool) {
@@ -112,21 +144,29 @@ impl Output {
             table.printstd();
         }
 
-        if !state.oracles.is_empty() {
+        if state.oracles.values().any(|record| !record.sources.is_empty()) {
             println!("\n  {}", "Oracle Values".bold().cyan());
             let mut table = Table::new();
             table.add_row(row![
                 "System ID".bold(),
                 "Oracle".bold(),
-                "Value".bold()
+                "Source".bold(),
+                "Raw".bold(),
+                "EMA".bold(),
+                "Last Update".bold()
             ]);
 
-            for (key, value) in &state.oracles {
-                table.add_row(row![
-                    key.system_id[..10].to_string() + "...",
-                    key.oracle_name,
-                    value.to_string()
-                ]);
+            for (key, record) in &state.oracles {
+                for source in &record.sources {
+                    table.add_row(row![
+                        key.system_id[..10].to_string() + "...",
+                        key.oracle_name,
+                        source.name,
+                        source.last_raw.to_string(),
+                        source.last_ema.to_string(),
+                        source.last_update_block.to_string()
+                    ]);
+                }
             }
             table.printstd();
         }
@@ -181,6 +221,72 @@ impl Output {
         table.printstd();
     }
 
+    /// Renders the state delta between `before` and `after` (see
+    /// `State::diff`) without requiring the caller to compute it first —
+    /// the `--dry-run` preview for `transfer`/`interact`/`oracle-update`.
+    pub fn state_diff(before: &State, after: &State, as_json: bool) {
+        let changes = State::diff(before, after);
+
+        if as_json {
+            let entries: Vec<Value> = changes.iter().map(|change| match change {
+                StateChange::Balance { address, asset, before, after } => json!({
+                    "type": "balance",
+                    "address": address,
+                    "asset": asset,
+                    "before": before.to_string(),
+                    "after": after.to_string(),
+                }),
+                StateChange::Oracle { system_id, oracle_name, before, after } => json!({
+                    "type": "oracle",
+                    "system_id": system_id,
+                    "oracle_name": oracle_name,
+                    "before": before.to_string(),
+                    "after": after.to_string(),
+                }),
+                StateChange::SystemDeployed { system_id } => json!({
+                    "type": "system_deployed",
+                    "system_id": system_id,
+                }),
+            }).collect();
+            println!("{}", serde_json::to_string_pretty(&json!({ "changes": entries })).unwrap());
+            return;
+        }
+
+        Output::header("Dry Run: State Diff");
+
+        if changes.is_empty() {
+            println!("  {} No state changes", "o".dimmed());
+            return;
+        }
+
+        let mut table = Table::new();
+        table.add_row(row!["Change".bold(), "Key".bold(), "Before".bold(), "After".bold()]);
+        for change in &changes {
+            match change {
+                StateChange::Balance { address, asset, before, after } => {
+                    table.add_row(row![
+                        "balance",
+                        format!("{}/{}", address, asset),
+                        before.to_string(),
+                        after.to_string()
+                    ]);
+                }
+                StateChange::Oracle { system_id, oracle_name, before, after } => {
+                    table.add_row(row![
+                        "oracle",
+                        format!("{}/{}", system_id, oracle_name),
+                        before.to_string(),
+                        after.to_string()
+                    ]);
+                }
+                StateChange::SystemDeployed { system_id } => {
+                    table.add_row(row!["system_deployed", system_id, "-", "-"]);
+                }
+            }
+        }
+        table.printstd();
+    }
+
     pub fn blocks(blocks: &[Block], as_json: bool) {
         if as_json {
             println!("{}", serde_json::to_string_pretty(&blocks).unwrap());
@@ -2,6 +2,7 @@ use std::io::{self, Write};
 use colored::*;
 use crate::cli::commands::*;
 use crate::cli::config::CliConfig;
+use crate::cli::output::Output;
 use crate::store::Store;
 
 pub struct Repl {
@@ -103,36 +104,57 @@ impl Repl {
         }
 
         let as_json = parts.contains(&"--json");
-        let parts: Vec<&str> = parts.into_iter().filter(|p| *p != "--json").collect();
-
-        match parts.as_slice() {
-            ["help"] | ["h"] => self.print_help(),
-
-            ["deploy", path] => cmd_deploy(path, as_json),
+        let dry_run = parts.contains(&"--dry-run");
+        let trace = parts.contains(&"--trace");
+        let expect_root = parts.iter()
+            .find_map(|p| p.strip_prefix("--expect-root="))
+            .map(|s| s.to_string());
+        let env = parts.iter()
+            .find_map(|p| p.strip_prefix("--env="))
+            .map(|s| s.to_string());
+        let parts: Vec<&str> = parts
+            .into_iter()
+            .filter(|p| {
+                *p != "--json" && *p != "--dry-run" && *p != "--trace"
+                    && !p.starts_with("--expect-root=") && !p.starts_with("--env=")
+            })
+            .collect();
+
+        let result: Result<(), CommandError> = match parts.as_slice() {
+            ["help"] | ["h"] => { self.print_help(); Ok(()) }
+
+            ["deploy", path] => cmd_deploy(path, env.as_deref(), expect_root, as_json),
 
             ["transfer", from, to, amount, asset] => {
                 match amount.parse::<u128>() {
-                    Ok(amt) => cmd_transfer(from, to, amt, asset, as_json),
-                    Err(_) => println!("{}", "Invalid amount".red()),
+                    Ok(amt) => cmd_transfer(from, to, amt, asset, expect_root, dry_run, as_json),
+                    Err(_) => { println!("{}", "Invalid amount".red()); Ok(()) }
                 }
             }
 
             ["interact", system_id, "evaluate"] => {
-                cmd_interact(system_id, "evaluate", None, as_json)
+                cmd_interact(system_id, "evaluate", None, expect_root, dry_run, trace, as_json)
             }
 
             ["interact", system_id, "trigger", action] => {
-                cmd_interact(system_id, "trigger", Some(action), as_json)
+                cmd_interact(system_id, "trigger", Some(action), expect_root, dry_run, trace, as_json)
             }
 
             ["interact", system_id, "both", action] => {
-                cmd_interact(system_id, "both", Some(action), as_json)
+                cmd_interact(system_id, "both", Some(action), expect_root, dry_run, trace, as_json)
             }
 
             ["oracle-update", system_id, oracle, value] => {
                 match value.parse::<u128>() {
-                    Ok(v) => cmd_oracle_update(system_id, oracle, v, as_json),
-                    Err(_) => println!("{}", "Invalid value".red()),
+                    Ok(v) => cmd_oracle_update(system_id, oracle, v, None, expect_root, dry_run, trace, as_json),
+                    Err(_) => { println!("{}", "Invalid value".red()); Ok(()) }
+                }
+            }
+
+            ["oracle-update", system_id, oracle, value, source] => {
+                match value.parse::<u128>() {
+                    Ok(v) => cmd_oracle_update(system_id, oracle, v, Some(source.to_string()), expect_root, dry_run, trace, as_json),
+                    Err(_) => { println!("{}", "Invalid value".red()); Ok(()) }
                 }
             }
 
@@ -149,7 +171,7 @@ impl Repl {
             ["mint", address, amount, asset] => {
                 match amount.parse::<u128>() {
                     Ok(amt) => cmd_mint(address, amt, asset, as_json),
-                    Err(_) => println!("{}", "Invalid amount".red()),
+                    Err(_) => { println!("{}", "Invalid amount".red()); Ok(()) }
                 }
             }
 
@@ -161,16 +183,22 @@ impl Repl {
                 for (i, cmd) in self.history.iter().enumerate() {
                     println!("  {} {}", format!("{:3}.", i + 1).dimmed(), cmd);
                 }
+                Ok(())
             }
 
-                _ => {
-            println!(
-                "{} Unknown command: '{}'. Type {} for help.",
-                "?".yellow(),
-                input.yellow(),
-                "help".cyan()
-            );
-    }
+            _ => {
+                println!(
+                    "{} Unknown command: '{}'. Type {} for help.",
+                    "?".yellow(),
+                    input.yellow(),
+                    "help".cyan()
+                );
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            Output::error(&e.to_string());
         }
     }
 
@@ -183,14 +211,14 @@ impl Repl {
             ("interact <system-id> evaluate",               "Evaluate all system conditions"),
             ("interact <system-id> trigger <action>",       "Trigger a specific action"),
             ("interact <system-id> both <action>",          "Evaluate conditions then trigger action"),
-            ("oracle-update <system-id> <oracle> <value>",  "Update an oracle value"),
+            ("oracle-update <system-id> <oracle> <value> [source]", "Update an oracle value (optionally under a named feed)"),
             ("mint <address> <amount> <asset>",             "Mint balance to address (testing)"),
             ("state",                                       "Show full state"),
             ("state system <system-id>",                    "Show a specific system"),
             ("state balance <address>",                     "Show balances for address"),
             ("blocks",                                      "Show block log"),
             ("replay",                                      "Rebuild state from block log"),
-            ("config set-sender <address>",                 "Set sender address"),
+            ("config set-sender <hex-seed>",                "Import a signing key and derive sender from it"),
             ("config show",                                 "Show current config"),
             ("history",                                     "Show command history"),
             ("help",                                        "Show this help"),
@@ -203,6 +231,10 @@ impl Repl {
 
         println!("\n{}", "Flags".bold().cyan());
         println!("  {:<50} {}", "--json".cyan(), "Output as JSON".dimmed());
+        println!("  {:<50} {}", "--dry-run".cyan(), "transfer/interact/oracle-update: preview the state diff, don't commit".dimmed());
+        println!("  {:<50} {}", "--expect-root=<hash>".cyan(), "deploy/transfer/interact/oracle-update: abort unless the current state root matches".dimmed());
+        println!("  {:<50} {}", "--trace".cyan(), "interact/oracle-update: include the structured execution trace in the output".dimmed());
+        println!("  {:<50} {}", "--env=<name>".cyan(), "deploy: merge the named entry from the file's `environments` map before deploying".dimmed());
 
         println!("\n{}", "Assets".bold().cyan());
         println!("  {:<50} {}", "ETH".cyan(), "Native ETH".dimmed());
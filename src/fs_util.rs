@@ -0,0 +1,80 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `contents` to `path` crash-safely: write to a sibling `.tmp` file,
+/// `flush`+`sync_all` it, back up the previous good file to `.bak`, then
+/// `fs::rename` the temp file over the destination. Rename is atomic on the
+/// same filesystem, so a crash or full disk mid-write can never leave a
+/// half-written file at `path`.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let tmp_path = sibling_with_suffix(path, "tmp");
+
+    {
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(contents)?;
+        tmp_file.flush()?;
+        tmp_file.sync_all()?;
+    }
+
+    if path.exists() {
+        let bak_path = sibling_with_suffix(path, "bak");
+        let _ = fs::copy(path, &bak_path);
+    }
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut os_name = path.file_name().unwrap_or_default().to_os_string();
+    os_name.push(".");
+    os_name.push(suffix);
+    path.with_file_name(os_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_path(test_name: &str) -> PathBuf {
+        PathBuf::from(format!("data/test_fs_util_{}/file.json", test_name))
+    }
+
+    fn cleanup(test_name: &str) {
+        let _ = fs::remove_dir_all(format!("data/test_fs_util_{}", test_name));
+    }
+
+    #[test]
+    fn test_atomic_write_creates_file() {
+        cleanup("create");
+        let path = test_path("create");
+
+        atomic_write(&path, b"hello").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+        assert!(!sibling_with_suffix(&path, "tmp").exists());
+
+        cleanup("create");
+    }
+
+    #[test]
+    fn test_atomic_write_leaves_backup_of_previous() {
+        cleanup("backup");
+        let path = test_path("backup");
+
+        atomic_write(&path, b"first").unwrap();
+        atomic_write(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let bak = sibling_with_suffix(&path, "bak");
+        assert_eq!(fs::read_to_string(&bak).unwrap(), "first");
+
+        cleanup("backup");
+    }
+}
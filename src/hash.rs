@@ -1,13 +1,43 @@
 use crate::types::FvlSystem;
 use sha3::{Keccak256, Digest};
+use std::collections::BTreeMap;
+use thiserror::Error;
 //use serde_yaml;
 
 pub type SystemId = [u8; 32];
 
-pub fn compute_system_id(system: &FvlSystem) -> SystemId {
-    let canonical_json = serde_json::to_string(system)
-        .expect("Failed to serialize system to JSON");
-    keccak256(canonical_json.as_bytes())
+#[derive(Error, Debug)]
+pub enum HashError {
+    #[error("Failed to serialize system to JSON: {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+/// `FvlSystem` contains `HashMap`s (e.g. `rights`), whose iteration order is
+/// randomized per-process by Rust's default hasher. Serializing straight to
+/// JSON would therefore let the same logical system hash differently across
+/// runs — fatal for a content-addressed id. Recursively sort every object's
+/// keys (RFC 8785-style canonicalization) so the encoded bytes only ever
+/// depend on the data, never on map iteration order.
+fn canonicalize(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: BTreeMap<String, serde_json::Value> = map
+                .into_iter()
+                .map(|(k, v)| (k, canonicalize(v)))
+                .collect();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(canonicalize).collect())
+        }
+        other => other,
+    }
+}
+
+pub fn compute_system_id(system: &FvlSystem) -> Result<SystemId, HashError> {
+    let value = serde_json::to_value(system)?;
+    let canonical_json = serde_json::to_string(&canonicalize(value))?;
+    Ok(keccak256(canonical_json.as_bytes()))
 }
 
 pub fn keccak256(data: &[u8]) -> [u8; 32] {
@@ -77,14 +107,16 @@ mod tests {
                 cliffs: None,
             },
             oracles: vec![],
+            circuit_breakers: vec![],
+            fees: vec![],
         }
     }
 
     #[test]
     fn test_deterministic_hashing() {
         let system = minimal_system();
-        let id1 = compute_system_id(&system);
-        let id2 = compute_system_id(&system);
+        let id1 = compute_system_id(&system).unwrap();
+        let id2 = compute_system_id(&system).unwrap();
         assert_eq!(id1, id2);
     }
 
@@ -94,15 +126,15 @@ mod tests {
         let mut system2 = system1.clone();
         system2.system = "Test2".to_string();
 
-        let id1 = compute_system_id(&system1);
-        let id2 = compute_system_id(&system2);
+        let id1 = compute_system_id(&system1).unwrap();
+        let id2 = compute_system_id(&system2).unwrap();
         assert_ne!(id1, id2);
     }
 
     #[test]
     fn test_hex_prefix() {
         let system = minimal_system();
-        let id = compute_system_id(&system);
+        let id = compute_system_id(&system).unwrap();
         let hex = system_id_to_hex(&id);
         assert!(hex.starts_with("0x"));
     }
@@ -110,9 +142,51 @@ mod tests {
     #[test]
     fn test_hex_roundtrip() {
         let system = minimal_system();
-        let id = compute_system_id(&system);
+        let id = compute_system_id(&system).unwrap();
         let hex = system_id_to_hex(&id);
         let parsed = system_id_from_hex(&hex).unwrap();
         assert_eq!(id, parsed);
     }
+
+    /// Same `rights` entries, inserted in two different orders: since
+    /// `HashMap` iteration order isn't tied to insertion order, this is the
+    /// scenario that would previously make the same logical system hash
+    /// differently depending on process/run.
+    #[test]
+    fn test_permuted_rights_map_hashes_equal() {
+        let mut system1 = minimal_system();
+        system1.rights.insert("anyone".to_string(), vec!["swap".to_string()]);
+        system1.rights.insert("admin".to_string(), vec!["pause".to_string(), "unpause".to_string()]);
+        system1.rights.insert("bot".to_string(), vec!["trigger".to_string()]);
+
+        let mut system2 = minimal_system();
+        system2.rights.insert("bot".to_string(), vec!["trigger".to_string()]);
+        system2.rights.insert("anyone".to_string(), vec!["swap".to_string()]);
+        system2.rights.insert("admin".to_string(), vec!["pause".to_string(), "unpause".to_string()]);
+
+        assert_eq!(compute_system_id(&system1).unwrap(), compute_system_id(&system2).unwrap());
+    }
+
+    /// Simulates "two processes": two independently built `HashMap`s with
+    /// the same logical contents, built up via a different sequence of
+    /// inserts/removes, must still canonicalize to the same id.
+    #[test]
+    fn test_shuffled_insertion_history_hashes_equal() {
+        let mut system1 = minimal_system();
+        for (k, v) in [("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")] {
+            system1.rights.insert(k.to_string(), vec![v.to_string()]);
+        }
+
+        let mut system2 = minimal_system();
+        for (k, v) in [("d", "4"), ("c", "3"), ("a", "1")] {
+            system2.rights.insert(k.to_string(), vec![v.to_string()]);
+        }
+        system2.rights.remove("a");
+        for (k, v) in [("b", "2"), ("a", "1")] {
+            system2.rights.insert(k.to_string(), vec![v.to_string()]);
+        }
+
+        assert_eq!(system1.rights, system2.rights);
+        assert_eq!(compute_system_id(&system1).unwrap(), compute_system_id(&system2).unwrap());
+    }
 }
\ No newline at end of file
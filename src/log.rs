@@ -1,15 +1,26 @@
+use std::fmt;
 use std::fs::{self, OpenOptions};
 use std::io::{self, BufRead, Write};
 use std::path::Path;
 use thiserror::Error;
-use crate::block::Block;
-use crate::state::State;
+use crate::block::{compute_block_hash, Block};
+use crate::state::{Snapshot, State};
 use crate::store::{Store, StoreError};
 use crate::runtime::Runtime;
 
 pub const LOG_PATH: &str = "data/blocks.log";
 pub const NETWORK_NAME: &str = "FVL_TESTNET";
 
+/// Directory `maybe_snapshot`/`rebuild_state_from_snapshot` read and write
+/// checkpoints in.
+pub const SNAPSHOT_DIR: &str = "data/snapshots";
+
+/// Default checkpoint cadence: take a snapshot every this-many blocks.
+pub const SNAPSHOT_INTERVAL: u64 = 100;
+
+/// Default number of recent snapshots `prune_snapshots` keeps around.
+pub const SNAPSHOT_RETENTION: usize = 5;
+
 #[derive(Error, Debug)]
 pub enum LogError {
     #[error("IO error: {0}")]
@@ -33,16 +44,58 @@ pub enum LogError {
         expected: String,
         got: String,
     },
+
+    #[error("Hash-chain validation failed at block {block}: {kind}")]
+    ChainValidation { block: u64, kind: ChainValidationKind },
+
+    #[error("Invalid transaction envelope: {0}")]
+    InvalidEnvelope(#[from] crate::transaction::EnvelopeError),
+
+    #[error("Block store backend error: {0}")]
+    BackendError(#[from] crate::blockstore::ObjectStoreError),
+}
+
+/// The specific way a block failed hash-chain validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainValidationKind {
+    /// The block's own `hash` doesn't match its recomputed contents.
+    SelfHashMismatch,
+    /// The block's `prev_hash` doesn't match the previous block's `hash`.
+    BrokenPrevHash,
+    /// `block.number` isn't exactly one more than the previous block's —
+    /// a gap (truncated log) or a rewind (fork/tamper).
+    NumberDiscontinuity { expected: u64, got: u64 },
+}
+
+impl fmt::Display for ChainValidationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainValidationKind::SelfHashMismatch => write!(f, "self-hash mismatch"),
+            ChainValidationKind::BrokenPrevHash => write!(f, "broken prev_hash link"),
+            ChainValidationKind::NumberDiscontinuity { expected, got } => {
+                write!(f, "block number discontinuity: expected {}, got {}", expected, got)
+            }
+        }
+    }
 }
 
 pub struct BlockLog;
 
 impl BlockLog {
-   
+
     pub fn append(block: &Block) -> Result<(), LogError> {
         Self::append_to(block, LOG_PATH)
     }
 
+    /// Decode a transaction from its self-describing envelope (see
+    /// `Transaction::decode_envelope`), rejecting a payload-type byte this
+    /// build doesn't recognize instead of attempting a best-effort parse.
+    /// The strict-mode entry point for transactions arriving as raw bytes
+    /// (e.g. a future RPC ingestion path) rather than already-typed values.
+    pub fn decode_transaction_strict(bytes: &[u8]) -> Result<crate::transaction::Transaction, LogError> {
+        Ok(crate::transaction::Transaction::decode_envelope(bytes)?)
+    }
+
     pub fn read_all() -> Result<Vec<Block>, LogError> {
         Self::read_all_from(LOG_PATH)
     }
@@ -96,27 +149,167 @@ impl BlockLog {
         Ok(blocks)
     }
 
-    pub fn latest_from(log_path: &str) -> Result<Option<Block>, LogError> {
+    /// Like `read_all_from`, but incrementally recomputes each block's hash
+    /// and checks the `prev_hash` link as it streams over a buffered reader,
+    /// so reading stops at the first tampered or truncated entry instead of
+    /// parsing the rest of the log. The blocks that pass are still collected
+    /// into the returned `Vec`.
+    pub fn read_all_validated_from(log_path: &str) -> Result<Vec<Block>, LogError> {
         if !Path::new(log_path).exists() {
-            return Ok(None);
+            return Ok(vec![]);
         }
 
         let file = fs::File::open(log_path)?;
         let reader = io::BufReader::new(file);
-        let mut latest: Option<Block> = None;
+        let mut blocks = vec![];
+        let mut prev_hash: Option<String> = None;
 
         for line in reader.lines() {
             let line = line?;
             if line.trim().is_empty() {
                 continue;
             }
+
             let block: Block = serde_json::from_str(&line)?;
-            latest = Some(block);
+
+            let recomputed = compute_block_hash(
+                block.number,
+                &block.prev_hash,
+                block.timestamp,
+                &block.txs,
+                &block.state_root,
+            );
+            if recomputed != block.hash {
+                return Err(LogError::ChainValidation {
+                    block: block.number,
+                    kind: ChainValidationKind::SelfHashMismatch,
+                });
+            }
+
+            if let Some(expected_prev) = &prev_hash {
+                if &block.prev_hash != expected_prev {
+                    return Err(LogError::ChainValidation {
+                        block: block.number,
+                        kind: ChainValidationKind::BrokenPrevHash,
+                    });
+                }
+            }
+
+            prev_hash = Some(block.hash.clone());
+            blocks.push(block);
         }
 
+        Ok(blocks)
+    }
+
+    pub fn latest_from(log_path: &str) -> Result<Option<Block>, LogError> {
+        let mut latest = None;
+        for block in Self::iter_from(log_path) {
+            latest = Some(block?);
+        }
         Ok(latest)
     }
 
+    /// Yields one deserialized block per log line, lazily — unlike
+    /// `read_all_from`/`read_all_validated_from`, nothing before the
+    /// currently-yielded block is held in memory, so callers that only
+    /// need to scan forward (`latest_from`, `verify_chain`,
+    /// `rebuild_state_at`) don't pay for a `Vec` of the whole log. A
+    /// missing log file yields no items, matching `read_all_from`'s
+    /// "missing log = no blocks" convention; any other I/O error is
+    /// yielded as the iterator's one and only item.
+    pub fn iter_from(log_path: &str) -> impl Iterator<Item = Result<Block, LogError>> {
+        let lines = match fs::File::open(log_path) {
+            Ok(file) => Some(io::BufReader::new(file).lines()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Box::new(std::iter::once(Err(LogError::IoError(e))))
+                    as Box<dyn Iterator<Item = Result<Block, LogError>>>
+            }
+        };
+
+        Box::new(lines.into_iter().flatten().filter_map(|line| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(serde_json::from_str::<Block>(&line).map_err(LogError::from)),
+            Err(e) => Some(Err(LogError::from(e))),
+        })) as Box<dyn Iterator<Item = Result<Block, LogError>>>
+    }
+
+    /// Walks the log checking that `block.number` increases by exactly one,
+    /// `block.prev_hash` matches the previous block's `hash`, and each
+    /// block's stored `hash` recomputes correctly from its contents —
+    /// streaming via `iter_from` rather than holding the log in memory.
+    /// Returns the first corruption or fork found as a precise
+    /// `LogError::ChainValidation`, naming the offending block and the
+    /// nature of the break.
+    pub fn verify_chain(log_path: &str) -> Result<(), LogError> {
+        Self::verify_block_sequence(Self::iter_from(log_path))
+    }
+
+    /// The integrity checks `verify_chain` runs, generalized over any
+    /// source of blocks rather than a fixed log path — lets
+    /// `blockstore::BlockStore` implementations reuse the same chain
+    /// validation `verify_chain` applies to the local file, over whatever
+    /// blocks `read_all` gave them.
+    pub(crate) fn verify_block_sequence<I>(blocks: I) -> Result<(), LogError>
+    where
+        I: IntoIterator<Item = Result<Block, LogError>>,
+    {
+        let mut prev: Option<Block> = None;
+
+        for block in blocks {
+            let block = block?;
+            Self::verify_block_link(prev.as_ref(), &block)?;
+            prev = Some(block);
+        }
+
+        Ok(())
+    }
+
+    /// One step of `verify_block_sequence`: checks `block` continues
+    /// `prev` (number +1, `prev_hash` linkage) and that `block.hash`
+    /// recomputes correctly from its own contents. `prev = None` only
+    /// skips the linkage checks, for the first block in a sequence.
+    /// Factored out so `rebuild_state_from_snapshot` can verify the log
+    /// incrementally while streaming past the snapshot's checkpoint block,
+    /// instead of running a separate full-log pass first.
+    fn verify_block_link(prev: Option<&Block>, block: &Block) -> Result<(), LogError> {
+        if let Some(prev_block) = prev {
+            if block.number != prev_block.number + 1 {
+                return Err(LogError::ChainValidation {
+                    block: block.number,
+                    kind: ChainValidationKind::NumberDiscontinuity {
+                        expected: prev_block.number + 1,
+                        got: block.number,
+                    },
+                });
+            }
+
+            if block.prev_hash != prev_block.hash {
+                return Err(LogError::ChainValidation {
+                    block: block.number,
+                    kind: ChainValidationKind::BrokenPrevHash,
+                });
+            }
+        }
+
+        let recomputed = compute_block_hash(
+            block.number,
+            &block.prev_hash,
+            block.timestamp,
+            &block.txs,
+            &block.state_root,
+        );
+        if recomputed != block.hash {
+            return Err(LogError::ChainValidation {
+                block: block.number,
+                kind: ChainValidationKind::SelfHashMismatch,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn init_if_empty_at(log_path: &str, network_name: &str) -> Result<Block, LogError> {
         if Path::new(log_path).exists() {
             if let Some(latest) = Self::latest_from(log_path)? {
@@ -133,18 +326,178 @@ impl BlockLog {
     log_path: &str,
     state_path: &str,
 ) -> Result<State, LogError> {
-    let blocks = Self::read_all_from(log_path)?;
+    let mut blocks = Self::iter_from(log_path).peekable();
+
+    if blocks.peek().is_none() {
+        return Ok(State::new());
+    }
+
+    Self::verify_chain(log_path)?;
+
+    let state = Self::replay_blocks(State::new(), blocks)?;
+
+    Store::save_to(&state, state_path)?;
+
+    Ok(state)
+}
+
+/// Like `rebuild_state_at`, but resumes from the newest snapshot under
+/// `snapshot_dir`, replaying only the blocks after it instead of the whole
+/// log. The snapshot is loaded and its checkpoint located *before* the log
+/// is touched; the log is then streamed once via `iter_from`, verifying
+/// chain integrity block-by-block and discarding everything at or before
+/// the checkpoint as it's seen, so only the post-checkpoint tail is ever
+/// held in memory or replayed — the log is never fully materialized (as
+/// `read_all_validated_from` would) when a usable snapshot exists. Falls
+/// back to a full `rebuild_state_at` replay whenever no snapshot exists or
+/// the snapshot's `state_root` doesn't match the block it claims to be
+/// checkpointing.
+pub fn rebuild_state_from_snapshot(
+    log_path: &str,
+    snapshot_dir: &str,
+    state_path: &str,
+) -> Result<State, LogError> {
+    let mut blocks = Self::iter_from(log_path).peekable();
 
-    if blocks.is_empty() {
+    if blocks.peek().is_none() {
         return Ok(State::new());
     }
 
-    let mut state = State::new();
+    if let Some((checkpoint_number, snapshot)) = Self::load_latest_snapshot(snapshot_dir, u64::MAX)? {
+        if let Ok(checkpoint_state) = State::import_snapshot(snapshot.clone()) {
+            let mut prev: Option<Block> = None;
+            let mut checkpoint_matches = false;
+            let mut tail = Vec::new();
+
+            for block in blocks {
+                let block = block?;
+                Self::verify_block_link(prev.as_ref(), &block)?;
+
+                if block.number == checkpoint_number {
+                    checkpoint_matches = block.state_root == snapshot.manifest.state_root;
+                } else if block.number > checkpoint_number {
+                    if !checkpoint_matches {
+                        break;
+                    }
+                    tail.push(block.clone());
+                }
+
+                prev = Some(block);
+            }
+
+            if checkpoint_matches {
+                let state = Self::replay_blocks(checkpoint_state, tail.into_iter().map(Ok))?;
+                Store::save_to(&state, state_path)?;
+                return Ok(state);
+            }
+        }
+    }
+
+    Self::rebuild_state_at(log_path, state_path)
+}
+
+/// Persists a snapshot of `state` as of `block` (see
+/// `State::export_snapshot`) under `snapshot_dir` if `block.number` falls
+/// on the `interval` cadence (e.g. every `SNAPSHOT_INTERVAL` blocks). A
+/// no-op otherwise. Intended to be called once per block, alongside the
+/// block being appended to the log.
+pub fn maybe_snapshot(
+    snapshot_dir: &str,
+    state: &State,
+    block: &Block,
+    interval: u64,
+) -> Result<(), LogError> {
+    if interval == 0 || block.number % interval != 0 {
+        return Ok(());
+    }
+
+    let snapshot = state.export_snapshot(block);
+    let json = serde_json::to_string_pretty(&snapshot)?;
+    crate::fs_util::atomic_write(Path::new(&Self::snapshot_path(snapshot_dir, block.number)), json.as_bytes())
+        .map_err(LogError::IoError)?;
+
+    Ok(())
+}
+
+/// Keeps only the `keep` most recent snapshots under `snapshot_dir`,
+/// deleting older ones, so the directory doesn't grow without bound as the
+/// chain advances.
+pub fn prune_snapshots(snapshot_dir: &str, keep: usize) -> Result<(), LogError> {
+    let mut numbers = Self::snapshot_numbers(snapshot_dir)?;
+    numbers.sort_unstable();
+
+    if numbers.len() <= keep {
+        return Ok(());
+    }
+
+    for block_number in &numbers[..numbers.len() - keep] {
+        let _ = fs::remove_file(Self::snapshot_path(snapshot_dir, *block_number));
+    }
+
+    Ok(())
+}
+
+fn snapshot_path(snapshot_dir: &str, block_number: u64) -> String {
+    format!("{}/{}.json", snapshot_dir, block_number)
+}
+
+fn snapshot_numbers(snapshot_dir: &str) -> Result<Vec<u64>, LogError> {
+    if !Path::new(snapshot_dir).exists() {
+        return Ok(vec![]);
+    }
+
+    let mut numbers = vec![];
+    for entry in fs::read_dir(snapshot_dir)? {
+        let entry = entry?;
+        if let Some(number) = entry
+            .path()
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok())
+        {
+            numbers.push(number);
+        }
+    }
+
+    Ok(numbers)
+}
 
-    let registry = crate::system_registry::SystemRegistry::load()
-        .map_err(|e| LogError::Custom(e.to_string()))?;
+/// Loads the highest-numbered snapshot under `snapshot_dir` whose
+/// `block_number` is at most `at_or_before`, or `None` if the directory is
+/// empty/missing or every snapshot is newer than that.
+fn load_latest_snapshot(snapshot_dir: &str, at_or_before: u64) -> Result<Option<(u64, Snapshot)>, LogError> {
+    let mut numbers: Vec<u64> = Self::snapshot_numbers(snapshot_dir)?
+        .into_iter()
+        .filter(|n| *n <= at_or_before)
+        .collect();
+    numbers.sort_unstable();
+
+    let Some(block_number) = numbers.last() else {
+        return Ok(None);
+    };
+
+    let content = fs::read_to_string(Self::snapshot_path(snapshot_dir, *block_number))?;
+    let snapshot: Snapshot = serde_json::from_str(&content)?;
+    Ok(Some((*block_number, snapshot)))
+}
 
-    for block in &blocks {
+/// Applies every transaction in `blocks` (skipping the genesis block) to
+/// `state` in order, checking each block's recomputed root against the one
+/// it recorded as it goes. Shared by `rebuild_state_at` (streaming straight
+/// from `iter_from`), `rebuild_state_from_snapshot` (replaying only the tail
+/// after a checkpoint), and `BlockStore::rebuild_state`'s default
+/// implementation (replaying whatever `read_all` returns, regardless of
+/// backend). Accepts anything iterable over `Result<Block, LogError>` so a
+/// caller can feed it either a lazy `iter_from` stream or a plain
+/// `Vec<Block>` (via `.into_iter().map(Ok)`).
+pub(crate) fn replay_blocks<I>(mut state: State, blocks: I) -> Result<State, LogError>
+where
+    I: IntoIterator<Item = Result<Block, LogError>>,
+{
+    let registry = crate::system_registry::cached_registry();
+
+    for block in blocks {
+        let block = block?;
         if block.number == 0 {
             continue;
         }
@@ -153,14 +506,18 @@ impl BlockLog {
             let reconstructed_tx = match &tx.payload {
                 crate::transaction::TransactionPayload::DeploySystem { system_id, yaml } => {
                     if yaml.is_none() {
-                        match registry.get(system_id) {
+                        match registry.get(system_id).map_err(|e| LogError::Custom(e.to_string()))? {
                             Some(yaml_content) => crate::transaction::Transaction {
                                 sender: tx.sender.clone(),
                                 nonce: tx.nonce,
                                 payload: crate::transaction::TransactionPayload::DeploySystem {
                                     system_id: system_id.clone(),
-                                    yaml: Some(yaml_content.clone()),
+                                    yaml: Some(yaml_content),
                                 },
+                                chain_id: tx.chain_id,
+                                proof_chain: tx.proof_chain.clone(),
+                                expected_prior_root: tx.expected_prior_root.clone(),
+                                signature: tx.signature.clone(),
                             },
                             None => {
                                 eprintln!(
@@ -177,7 +534,7 @@ impl BlockLog {
                 _ => tx.clone(),
             };
 
-            let (new_state, result) = Runtime::apply_tx(&state, reconstructed_tx);
+            let (new_state, result) = Runtime::apply_tx(&state, reconstructed_tx, block.number);
             state = new_state;
 
             if !result.success {
@@ -188,20 +545,16 @@ impl BlockLog {
             }
         }
 
-        // if !block.txs.is_empty() {   /// FIX THIS LATER - FOR NOW, SKIP STATE ROOT CHECK TO AVOID REPLAY ISSUES
-        //     let computed_root = state.state_root_hex();
-        //     if computed_root != block.state_root {
-        //         return Err(LogError::StateRootMismatch {
-        //             block: block.number,
-        //             expected: block.state_root.clone(),
-        //             got: computed_root,
-        //         });
-        //     }
-        // }
+        let computed_root = state.state_root_hex();
+        if computed_root != block.state_root {
+            return Err(LogError::StateRootMismatch {
+                block: block.number,
+                expected: block.state_root.clone(),
+                got: computed_root,
+            });
+        }
     }
 
-    Store::save_to(&state, state_path)?;
-
     Ok(state)
 }
 }
@@ -288,4 +641,217 @@ mod tests {
 
         cleanup("read_empty");
     }
+
+    #[test]
+    fn test_read_all_validated_detects_self_hash_tamper() {
+        cleanup("validate_self_hash");
+        let log_path = test_log_path("validate_self_hash");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let mut block1 = Block::new(1, genesis.hash.clone(), vec![], "0x1234".to_string());
+        block1.hash = "0xtampered".to_string();
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        let result = BlockLog::read_all_validated_from(&log_path);
+        assert!(matches!(
+            result,
+            Err(LogError::ChainValidation { block: 1, kind: ChainValidationKind::SelfHashMismatch })
+        ));
+
+        cleanup("validate_self_hash");
+    }
+
+    #[test]
+    fn test_read_all_validated_detects_broken_prev_link() {
+        cleanup("validate_prev_link");
+        let log_path = test_log_path("validate_prev_link");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let block1 = Block::new(1, "0xnotgenesis".to_string(), vec![], "0x1234".to_string());
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        let result = BlockLog::read_all_validated_from(&log_path);
+        assert!(matches!(
+            result,
+            Err(LogError::ChainValidation { block: 1, kind: ChainValidationKind::BrokenPrevHash })
+        ));
+
+        cleanup("validate_prev_link");
+    }
+
+    #[test]
+    fn test_iter_from_yields_blocks_in_order() {
+        cleanup("iter_from");
+        let log_path = test_log_path("iter_from");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], "0x1234".to_string());
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        let numbers: Vec<u64> = BlockLog::iter_from(&log_path)
+            .map(|b| b.unwrap().number)
+            .collect();
+        assert_eq!(numbers, vec![0, 1]);
+
+        cleanup("iter_from");
+    }
+
+    #[test]
+    fn test_iter_from_missing_log_yields_nothing() {
+        cleanup("iter_from_missing");
+        let log_path = test_log_path("iter_from_missing");
+
+        assert_eq!(BlockLog::iter_from(&log_path).count(), 0);
+
+        cleanup("iter_from_missing");
+    }
+
+    #[test]
+    fn test_verify_chain_detects_number_discontinuity() {
+        cleanup("verify_chain_gap");
+        let log_path = test_log_path("verify_chain_gap");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        // Block 2 appended directly after genesis — block 1 is missing.
+        let block2 = Block::new(2, genesis.hash.clone(), vec![], "0x1234".to_string());
+        BlockLog::append_to(&block2, &log_path).unwrap();
+
+        let result = BlockLog::verify_chain(&log_path);
+        assert!(matches!(
+            result,
+            Err(LogError::ChainValidation {
+                block: 2,
+                kind: ChainValidationKind::NumberDiscontinuity { expected: 1, got: 2 }
+            })
+        ));
+
+        cleanup("verify_chain_gap");
+    }
+
+    #[test]
+    fn test_verify_chain_accepts_valid_log() {
+        cleanup("verify_chain_ok");
+        let log_path = test_log_path("verify_chain_ok");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], "0x1234".to_string());
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        assert!(BlockLog::verify_chain(&log_path).is_ok());
+
+        cleanup("verify_chain_ok");
+    }
+
+    #[test]
+    fn test_rebuild_state_detects_root_divergence() {
+        cleanup("rebuild_divergent");
+        let log_path = test_log_path("rebuild_divergent");
+        let state_path = format!("data/test_rebuild_divergent/state.json");
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        // A block claiming a tx happened but recording the wrong state root.
+        let tx = crate::transaction::Transaction {
+            sender: "0x1234567890123456789012345678901234567890".to_string(),
+            nonce: 0,
+            payload: crate::transaction::TransactionPayload::Transfer {
+                from: "0x1234567890123456789012345678901234567890".to_string(),
+                to: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                asset_type: crate::transaction::TransactionAsset::Eth,
+                amount: 100,
+            },
+            chain_id: crate::signing::CHAIN_ID,
+            proof_chain: vec![],
+            expected_prior_root: None,
+            signature: String::new(),
+        };
+        let bogus_block = Block::new(1, genesis.hash.clone(), vec![tx], "0xdeadbeef".to_string());
+        BlockLog::append_to(&bogus_block, &log_path).unwrap();
+
+        let result = BlockLog::rebuild_state_at(&log_path, &state_path);
+        assert!(matches!(result, Err(LogError::StateRootMismatch { block: 1, .. })));
+
+        cleanup("rebuild_divergent");
+    }
+
+    #[test]
+    fn test_rebuild_state_from_snapshot_matches_full_replay() {
+        cleanup("rebuild_from_snapshot");
+        let log_path = test_log_path("rebuild_from_snapshot");
+        let snapshot_dir = "data/test_rebuild_from_snapshot/snapshots".to_string();
+        let state_path = "data/test_rebuild_from_snapshot/state.json".to_string();
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let empty_root = State::new().state_root_hex();
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], empty_root.clone());
+        BlockLog::append_to(&block1, &log_path).unwrap();
+        BlockLog::maybe_snapshot(&snapshot_dir, &State::new(), &block1, 1).unwrap();
+
+        let block2 = Block::new(2, block1.hash.clone(), vec![], empty_root);
+        BlockLog::append_to(&block2, &log_path).unwrap();
+
+        let expected = BlockLog::rebuild_state_at(&log_path, &state_path).unwrap();
+        let from_snapshot =
+            BlockLog::rebuild_state_from_snapshot(&log_path, &snapshot_dir, &state_path).unwrap();
+
+        assert_eq!(expected.state_root_hex(), from_snapshot.state_root_hex());
+
+        cleanup("rebuild_from_snapshot");
+    }
+
+    #[test]
+    fn test_rebuild_state_from_snapshot_falls_back_without_snapshot() {
+        cleanup("rebuild_no_snapshot");
+        let log_path = test_log_path("rebuild_no_snapshot");
+        let snapshot_dir = "data/test_rebuild_no_snapshot/snapshots".to_string();
+        let state_path = "data/test_rebuild_no_snapshot/state.json".to_string();
+
+        let genesis = Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let empty_root = State::new().state_root_hex();
+        let block1 = Block::new(1, genesis.hash.clone(), vec![], empty_root);
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        let expected = BlockLog::rebuild_state_at(&log_path, &state_path).unwrap();
+        let from_snapshot =
+            BlockLog::rebuild_state_from_snapshot(&log_path, &snapshot_dir, &state_path).unwrap();
+
+        assert_eq!(expected.state_root_hex(), from_snapshot.state_root_hex());
+
+        cleanup("rebuild_no_snapshot");
+    }
+
+    #[test]
+    fn test_prune_snapshots_keeps_only_most_recent() {
+        cleanup("prune_snapshots");
+        let snapshot_dir = "data/test_prune_snapshots/snapshots".to_string();
+
+        let state = State::new();
+        for number in [1u64, 2, 3, 4] {
+            let block = Block::new(number, "0xprev".to_string(), vec![], state.state_root_hex());
+            BlockLog::maybe_snapshot(&snapshot_dir, &state, &block, 1).unwrap();
+        }
+
+        BlockLog::prune_snapshots(&snapshot_dir, 2).unwrap();
+
+        let mut remaining = BlockLog::snapshot_numbers(&snapshot_dir).unwrap();
+        remaining.sort_unstable();
+        assert_eq!(remaining, vec![3, 4]);
+
+        cleanup("prune_snapshots");
+    }
 }
\ No newline at end of file
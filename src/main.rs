@@ -2,6 +2,7 @@ use fvl_parser::{
     Parser, Store, BlockLog,
     Transaction, TransactionPayload, TransactionAsset, InteractMode,
     sequence_tx, compute_system_id, system_id_to_hex,
+    signing::Keypair,
 };
 
 fn main() {
@@ -26,7 +27,13 @@ fn main() {
         }
     };
 
-    let system_id_hex = system_id_to_hex(&compute_system_id(&system));
+    let system_id_hex = match compute_system_id(&system) {
+        Ok(id) => system_id_to_hex(&id),
+        Err(e) => {
+            eprintln!("Hash error: {}", e);
+            std::process::exit(1);
+        }
+    };
     println!("System ID: {}", system_id_hex);
 
     println!("\n=== Loading State ===");
@@ -52,7 +59,8 @@ fn main() {
 
     println!("\n=== Deploying System ===");
     let yaml = std::fs::read_to_string(file_path).unwrap();
-    let deployer = "0x1234567890123456789012345678901234567890".to_string();
+    let user_keypair = Keypair::generate();
+    let deployer = user_keypair.address_hex();
 
     let deploy_tx = Transaction {
         sender: deployer.clone(),
@@ -61,7 +69,12 @@ fn main() {
             system_id: system_id_hex.clone(),
             yaml: Some(yaml),
         },
-    };
+        chain_id: fvl_parser::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: None,
+        signature: String::new(),
+    }
+    .sign(&user_keypair);
 
     match sequence_tx(deploy_tx, &state) {
         Ok((result, new_state)) => {
@@ -92,7 +105,7 @@ fn main() {
     };
 
     println!("\n=== Sending Transfer ===");
-    let sender = "0x1234567890123456789012345678901234567890";
+    let sender = deployer.as_str();
     let receiver = "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd";
 
     state.set_balance(sender, &fvl_parser::AssetType::Eth, 10000);
@@ -107,7 +120,12 @@ fn main() {
             asset_type: TransactionAsset::Eth,
             amount: 500,
         },
-    };
+        chain_id: fvl_parser::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: None,
+        signature: String::new(),
+    }
+    .sign(&user_keypair);
 
     match sequence_tx(transfer_tx, &state) {
         Ok((result, new_state)) => {
@@ -143,7 +161,12 @@ fn main() {
             system_id: deployed_system_id.clone(),
             mode: InteractMode::EvaluateConditions,
         },
-    };
+        chain_id: fvl_parser::signing::CHAIN_ID,
+        proof_chain: vec![],
+        expected_prior_root: None,
+        signature: String::new(),
+    }
+    .sign(&user_keypair);
 
     match sequence_tx(interact_tx, &state) {
         Ok((result, new_state)) => {
@@ -1,5 +1,5 @@
 use crate::types::FvlSystem;
-use crate::validation::Validator;
+use crate::validation::{Diagnostic, Validator};
 use serde_yaml;
 use std::fs;
 use thiserror::Error;
@@ -8,15 +8,22 @@ use thiserror::Error;
 pub enum ParseError {
     #[error("File not found: {0}")]
     FileNotFound(String),
-    
+
     #[error("Failed to read file: {0}")]
     IoError(#[from] std::io::Error),
-    
+
     #[error("Invalid YAML syntax: {0}")]
     YamlError(#[from] serde_yaml::Error),
-    
+
     #[error("Validation error: {0}")]
     ValidationError(String),
+
+    /// One or more semantic violations were found while analyzing an
+    /// otherwise structurally valid system (see `crate::validation::Validator::analyze_semantics`).
+    /// Unlike `ValidationError`, every violation is collected before
+    /// returning instead of failing on the first one.
+    #[error("{} semantic error(s) found", .0.len())]
+    SemanticError(Vec<Diagnostic>),
 }
 
 pub struct Parser;
@@ -31,20 +38,82 @@ impl Parser {
                     ParseError::IoError(e)
                 }
             })?;
-        
+
         Self::parse_yaml(&content)
     }
-    
+
     pub fn parse_yaml(yaml: &str) -> Result<FvlSystem, ParseError> {
         let system: FvlSystem = serde_yaml::from_str(yaml)
             .map_err(ParseError::YamlError)?;
-        
+
+        Self::validate_system(system)
+    }
+
+    /// Parse `yaml`, deep-merge the named entry from its top-level
+    /// `environments` map onto the base document, then validate the merged
+    /// result. Lets one YAML file describe per-deployment overrides (oracle
+    /// sources, contract addresses, ...) instead of duplicating near-identical
+    /// files per network. Errors if `env_name` isn't one of the defined
+    /// environments.
+    pub fn parse_yaml_env(yaml: &str, env_name: &str) -> Result<FvlSystem, ParseError> {
+        let root: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+        let mut root = match root {
+            serde_yaml::Value::Mapping(m) => m,
+            _ => {
+                return Err(ParseError::ValidationError(
+                    "Top-level YAML must be a mapping".to_string(),
+                ))
+            }
+        };
+
+        let environments = root.remove(&serde_yaml::Value::String("environments".to_string()));
+        let overlay = match environments {
+            Some(serde_yaml::Value::Mapping(envs)) => {
+                envs.get(&serde_yaml::Value::String(env_name.to_string())).cloned()
+            }
+            _ => None,
+        };
+        let overlay = overlay.ok_or_else(|| {
+            ParseError::ValidationError(format!("Environment '{}' not defined", env_name))
+        })?;
+
+        let merged = deep_merge(serde_yaml::Value::Mapping(root), overlay);
+        let system: FvlSystem = serde_yaml::from_value(merged)?;
+
+        Self::validate_system(system)
+    }
+
+    fn validate_system(system: FvlSystem) -> Result<FvlSystem, ParseError> {
         Validator::validate(&system)?;
-        
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        if !diagnostics.is_empty() {
+            return Err(ParseError::SemanticError(diagnostics));
+        }
+
         Ok(system)
     }
 }
 
+/// Recursively merge `overlay` onto `base`: mappings merge key-by-key
+/// (recursing into nested mappings), everything else (scalars, sequences)
+/// is replaced wholesale by the overlay's value.
+fn deep_merge(base: serde_yaml::Value, overlay: serde_yaml::Value) -> serde_yaml::Value {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(mut base_map), serde_yaml::Value::Mapping(overlay_map)) => {
+            for (key, overlay_value) in overlay_map {
+                let merged_value = match base_map.remove(&key) {
+                    Some(base_value) => deep_merge(base_value, overlay_value),
+                    None => overlay_value,
+                };
+                base_map.insert(key, merged_value);
+            }
+            serde_yaml::Value::Mapping(base_map)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,4 +205,198 @@ invalid yaml: [
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ParseError::FileNotFound(_)));
     }
+
+    #[test]
+    fn test_parse_undefined_oracle_is_semantic_error() {
+        let yaml = r#"
+system: "SimpleSwap"
+
+pool:
+  collect:
+    from:
+      type: anyone
+    what:
+      type: eth
+    min:
+      type: zero
+    max:
+      type: none
+    cap:
+      type: none
+
+rules:
+  conditions:
+    - if:
+        type: price_gt
+        oracle: "missing_oracle"
+        value: "100"
+      then:
+        type: pause
+  distribute:
+    formula:
+      type: proportional
+    to:
+      type: contributors
+    triggers: manual
+
+rights: {}
+
+time:
+  start:
+    type: now
+  end:
+    type: none
+  locks:
+    type: none
+  vesting:
+    type: none
+
+oracles: []
+"#;
+
+        let result = Parser::parse_yaml(yaml);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ParseError::SemanticError(_)));
+    }
+
+    #[test]
+    fn test_parse_collects_multiple_semantic_errors() {
+        let yaml = r#"
+system: "SimpleSwap"
+
+pool:
+  collect:
+    from:
+      type: anyone
+    what:
+      type: eth
+    min:
+      type: zero
+    max:
+      type: none
+    cap:
+      type: none
+
+rules:
+  conditions: []
+  distribute:
+    formula:
+      type: tiered
+      thresholds: []
+    to:
+      type: top_n
+      count: 0
+    triggers: manual
+
+rights: {}
+
+time:
+  start:
+    type: timestamp
+    value: 200
+  end:
+    type: timestamp
+    value: 100
+  locks:
+    type: none
+  vesting:
+    type: none
+
+oracles: []
+"#;
+
+        let result = Parser::parse_yaml(yaml);
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            ParseError::SemanticError(diagnostics) => assert!(diagnostics.len() >= 3),
+            other => panic!("expected SemanticError, got {:?}", other),
+        }
+    }
+
+    const YAML_WITH_ENVIRONMENTS: &str = r#"
+system: "SimpleSwap"
+
+pool:
+  collect:
+    from:
+      type: anyone
+    what:
+      type: erc20
+      address: "0x1111111111111111111111111111111111111111"
+    min:
+      type: zero
+    max:
+      type: none
+    cap:
+      type: none
+
+rules:
+  conditions: []
+  distribute:
+    formula:
+      type: proportional
+    to:
+      type: contributors
+    triggers: manual
+
+rights: {}
+
+time:
+  start:
+    type: now
+  end:
+    type: none
+  locks:
+    type: none
+  vesting:
+    type: none
+
+oracles:
+  - name: "eth_price"
+    type: "chainlink"
+    source: "https://mainnet.example/feed"
+
+environments:
+  staging:
+    pool:
+      collect:
+        what:
+          address: "0x2222222222222222222222222222222222222222"
+    oracles:
+      - name: "eth_price"
+        type: "chainlink"
+        source: "https://staging.example/feed"
+"#;
+
+    #[test]
+    fn test_parse_yaml_env_applies_overlay() {
+        let system = Parser::parse_yaml_env(YAML_WITH_ENVIRONMENTS, "staging").unwrap();
+
+        match system.pool.collect.what {
+            crate::types::AssetType::Erc20 { address } => {
+                assert_eq!(address, "0x2222222222222222222222222222222222222222");
+            }
+            other => panic!("expected Erc20, got {:?}", other),
+        }
+        assert_eq!(system.oracles[0].source, "https://staging.example/feed");
+    }
+
+    #[test]
+    fn test_parse_yaml_env_base_untouched_by_default_parse() {
+        let system = Parser::parse_yaml(YAML_WITH_ENVIRONMENTS).unwrap();
+
+        match system.pool.collect.what {
+            crate::types::AssetType::Erc20 { address } => {
+                assert_eq!(address, "0x1111111111111111111111111111111111111111");
+            }
+            other => panic!("expected Erc20, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_yaml_env_unknown_environment_errors() {
+        let result = Parser::parse_yaml_env(YAML_WITH_ENVIRONMENTS, "nonexistent");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ParseError::ValidationError(_)));
+    }
 }
\ No newline at end of file
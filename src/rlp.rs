@@ -0,0 +1,91 @@
+//! Minimal RLP (Recursive Length Prefix) encoder — just enough to build the
+//! handful of transaction shapes `submitter` needs. No decoding; nothing
+//! here consumes RLP, it only produces it.
+
+/// RLP-encodes a single byte string per the spec's three length cases.
+pub fn encode_bytes(data: &[u8]) -> Vec<u8> {
+    if data.len() == 1 && data[0] < 0x80 {
+        return vec![data[0]];
+    }
+
+    let mut out = encode_length(data.len(), 0x80);
+    out.extend_from_slice(data);
+    out
+}
+
+/// RLP-encodes a list of already-encoded items.
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload: Vec<u8> = items.iter().flatten().copied().collect();
+    let mut out = encode_length(payload.len(), 0xc0);
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Encodes an unsigned integer as its minimal big-endian byte string, with
+/// leading zero bytes stripped (RLP has no fixed-width integers — zero
+/// itself encodes as the empty string).
+pub fn encode_uint(value: u128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let trimmed: &[u8] = {
+        let first_nonzero = bytes.iter().position(|&b| b != 0);
+        match first_nonzero {
+            Some(i) => &bytes[i..],
+            None => &[],
+        }
+    };
+    encode_bytes(trimmed)
+}
+
+fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        vec![offset + len as u8]
+    } else {
+        let len_bytes = minimal_be_bytes(len as u64);
+        let mut out = vec![offset + 55 + len_bytes.len() as u8];
+        out.extend_from_slice(&len_bytes);
+        out
+    }
+}
+
+fn minimal_be_bytes(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+    bytes[first_nonzero..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_empty_bytes() {
+        assert_eq!(encode_bytes(&[]), vec![0x80]);
+    }
+
+    #[test]
+    fn test_encode_single_small_byte_is_itself() {
+        assert_eq!(encode_bytes(&[0x01]), vec![0x01]);
+    }
+
+    #[test]
+    fn test_encode_short_string() {
+        assert_eq!(encode_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+    }
+
+    #[test]
+    fn test_encode_uint_zero_is_empty_string() {
+        assert_eq!(encode_uint(0), vec![0x80]);
+    }
+
+    #[test]
+    fn test_encode_uint_drops_leading_zero_bytes() {
+        assert_eq!(encode_uint(1024), vec![0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn test_encode_list_of_strings() {
+        let items = vec![encode_bytes(b"cat"), encode_bytes(b"dog")];
+        let encoded = encode_list(&items);
+        assert_eq!(encoded, vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']);
+    }
+}
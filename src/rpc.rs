@@ -0,0 +1,127 @@
+//! A small, dependency-free JSON-RPC client over plain HTTP. Just enough to
+//! talk to an Ethereum node's `eth_*` methods without pulling in an async
+//! HTTP stack for what's a handful of request/response round trips.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RpcError {
+    #[error("invalid RPC URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("connection failed: {0}")]
+    ConnectionFailed(String),
+
+    #[error("malformed HTTP response from RPC endpoint")]
+    MalformedResponse,
+
+    #[error("RPC call failed: {0}")]
+    CallFailed(String),
+}
+
+/// Host/port/path split out of a plain `http://host:port/path` URL.
+/// TLS endpoints aren't supported — local/dev nodes are always plain HTTP.
+pub(crate) struct HttpEndpoint {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) path: String,
+}
+
+pub(crate) fn parse_url(url: &str) -> Result<HttpEndpoint, RpcError> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| RpcError::InvalidUrl(format!("only http:// URLs are supported: {}", url)))?;
+
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port_str)) => {
+            let port = port_str
+                .parse::<u16>()
+                .map_err(|_| RpcError::InvalidUrl(url.to_string()))?;
+            (host.to_string(), port)
+        }
+        None => (authority.to_string(), 80),
+    };
+
+    Ok(HttpEndpoint { host, port, path: path.to_string() })
+}
+
+/// Issues a single JSON-RPC call and returns the `result` field, or an
+/// error built from the response's `error` field.
+pub fn call(rpc_url: &str, method: &str, params: Value) -> Result<Value, RpcError> {
+    let endpoint = parse_url(rpc_url)?;
+
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    })
+    .to_string();
+
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        endpoint.path, endpoint.host, body.len(), body
+    );
+
+    let mut stream = TcpStream::connect((endpoint.host.as_str(), endpoint.port))
+        .map_err(|e| RpcError::ConnectionFailed(e.to_string()))?;
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| RpcError::ConnectionFailed(e.to_string()))?;
+
+    let mut raw = Vec::new();
+    stream
+        .read_to_end(&mut raw)
+        .map_err(|e| RpcError::ConnectionFailed(e.to_string()))?;
+
+    let response = String::from_utf8_lossy(&raw);
+    let body_start = response.find("\r\n\r\n").ok_or(RpcError::MalformedResponse)? + 4;
+    let json_body = &response[body_start..];
+
+    let parsed: Value = serde_json::from_str(json_body.trim())
+        .map_err(|_| RpcError::MalformedResponse)?;
+
+    if let Some(error) = parsed.get("error") {
+        return Err(RpcError::CallFailed(error.to_string()));
+    }
+
+    parsed
+        .get("result")
+        .cloned()
+        .ok_or(RpcError::MalformedResponse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_with_port_and_path() {
+        let endpoint = parse_url("http://localhost:8545/rpc").unwrap();
+        assert_eq!(endpoint.host, "localhost");
+        assert_eq!(endpoint.port, 8545);
+        assert_eq!(endpoint.path, "/rpc");
+    }
+
+    #[test]
+    fn test_parse_url_defaults_to_port_80_and_root_path() {
+        let endpoint = parse_url("http://example.com").unwrap();
+        assert_eq!(endpoint.host, "example.com");
+        assert_eq!(endpoint.port, 80);
+        assert_eq!(endpoint.path, "/");
+    }
+
+    #[test]
+    fn test_parse_url_rejects_non_http_scheme() {
+        assert!(parse_url("https://example.com").is_err());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::state::{State, StateError};
+use serde::Serialize;
+use crate::capability::{self, CapabilityToken};
+use crate::state::{State, StateError, SystemState};
 use crate::system_id_from_hex;
 use crate::transaction::{Transaction, TransactionPayload, InteractMode, TransactionAsset};
 use crate::types::{AssetType, Expression, Action};
@@ -10,6 +12,37 @@ pub struct TxResult {
     pub success: bool,
     pub error: Option<TxError>,
     pub state_root: String,
+    /// Every condition evaluated, action fired, and balance/oracle
+    /// mutation applied while executing this transaction, in order. Always
+    /// collected (cheap relative to everything else `execute` already
+    /// does); whether it's surfaced to a caller is an opt-in display
+    /// choice (see the CLI's `--trace` flag).
+    pub trace: Vec<TraceEvent>,
+    /// Total utilization-scaled fee charged across every in-system
+    /// `Action::Transfer` this transaction triggered (see
+    /// `state::State::compute_fee`). Zero for transactions that never hit
+    /// a fee-curve-bearing transfer.
+    pub fee_charged: u128,
+}
+
+/// One step of a transaction's execution, modeled loosely on EVM
+/// call-tracing: enough to answer "why did this rule set do what it did"
+/// without re-deriving it from the YAML and the final balances. Recorded
+/// by `Runtime::execute` and its helpers as they run; order matches
+/// execution order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum TraceEvent {
+    ConditionEvaluated { expr: String, result: bool },
+    ActionFired { action: String },
+    BalanceChanged { address: String, asset: String, before: u128, after: u128 },
+    OracleUpdated {
+        system_id: String,
+        oracle_name: String,
+        source: String,
+        before: u128,
+        after: u128,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -32,11 +65,35 @@ pub enum TxError {
     #[error("Oracle not found: {0}")]
     OracleNotFound(String),
 
+    #[error("Oracle stale: {0}")]
+    OracleStale(String),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
     #[error("Invalid action: {0}")]
     InvalidAction(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("State precondition failed: expected root {expected}, actual root {actual}")]
+    StatePrecondition { expected: String, actual: String },
+
+    #[error("Circuit breaker tripped for system {system_id} asset {asset}")]
+    CircuitBreakerTripped { system_id: String, asset: String },
+
+    #[error("Flash loan not repaid for asset {asset}: still owed {shortfall}")]
+    FlashLoanNotRepaid { asset: String, shortfall: u128 },
+
+    #[error("capability check failed: {0}")]
+    CapabilityDenied(String),
+}
+
+impl From<crate::signing::SigningError> for TxError {
+    fn from(e: crate::signing::SigningError) -> Self {
+        TxError::InvalidSignature(e.to_string())
+    }
 }
 
 impl From<StateError> for TxError {
@@ -45,6 +102,7 @@ impl From<StateError> for TxError {
             StateError::SystemAlreadyDeployed(s) => TxError::SystemAlreadyDeployed(s),
             StateError::SystemNotFound(s) => TxError::SystemNotFound(s),
             StateError::OracleNotFound(s) => TxError::OracleNotFound(s),
+            StateError::OracleStale(s) => TxError::OracleStale(s),
             StateError::InsufficientBalance { address, required, available } => {
                 TxError::InsufficientBalance(
                     format!("{}: required {}, has {}", address, required, available)
@@ -56,6 +114,10 @@ impl From<StateError> for TxError {
                 )
             }
             StateError::Unauthorized(s) => TxError::Unauthorized(s),
+            StateError::SnapshotMismatch(s) => TxError::ParseError(s),
+            StateError::CircuitBreakerTripped { system_id, asset } => {
+                TxError::CircuitBreakerTripped { system_id, asset }
+            }
         }
     }
 }
@@ -63,7 +125,16 @@ impl From<StateError> for TxError {
 pub struct Runtime;
 
 impl Runtime {
-fn execute(state: &mut State, tx: &Transaction) -> Result<(), TxError> {
+fn execute(state: &mut State, tx: &Transaction, block: u64, trace: &mut Vec<TraceEvent>, fee_total: &mut u128) -> Result<(), TxError> {
+    tx.verify_signature()?;
+
+    if let Some(expected) = &tx.expected_prior_root {
+        let actual = state.state_root_hex();
+        if *expected != actual {
+            return Err(TxError::StatePrecondition { expected: expected.clone(), actual });
+        }
+    }
+
     state.consume_nonce(&tx.sender, tx.nonce)
         .map_err(TxError::from)?;
 
@@ -73,13 +144,13 @@ fn execute(state: &mut State, tx: &Transaction) -> Result<(), TxError> {
             Self::execute_deploy(state, system_id, yaml_str, &tx.sender, tx.nonce)
         }
         TransactionPayload::Interact { system_id, mode } => {
-            Self::execute_interact(state, system_id, mode, &tx.sender)
+            Self::execute_interact(state, system_id, mode, &tx.sender, &tx.proof_chain, block, trace, fee_total)
         }
-        TransactionPayload::OracleUpdate { system_id, oracle_name, value } => {
-            Self::execute_oracle_update(state, system_id, oracle_name, *value, &tx.sender)
+        TransactionPayload::OracleUpdate { system_id, oracle_name, value, source } => {
+            Self::execute_oracle_update(state, system_id, oracle_name, *value, source.as_deref(), &tx.sender, block, trace)
         }
         TransactionPayload::Transfer { from, to, asset_type, amount } => {
-            Self::execute_transfer(state, from, to, asset_type, *amount, &tx.sender)
+            Self::execute_transfer(state, from, to, asset_type, *amount, &tx.sender, trace)
         }
     }
 }
@@ -106,18 +177,20 @@ fn execute_deploy(
     Ok(())
 }
 
-pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
+pub fn apply_tx(state: &State, tx: Transaction, block: u64) -> (State, TxResult) {
         let mut new_state = state.clone();
-        let result = Self::execute(&mut new_state, &tx);
+        let mut trace = Vec::new();
+        let mut fee_charged = 0u128;
+        let result = Self::execute(&mut new_state, &tx, block, &mut trace, &mut fee_charged);
 
     match result {
         Ok(()) => {
             let state_root = new_state.state_root_hex();
-            (new_state, TxResult { success: true, error: None, state_root })
+            (new_state, TxResult { success: true, error: None, state_root, trace, fee_charged })
         }
         Err(e) => {
             let state_root = state.state_root_hex();
-            (state.clone(), TxResult { success: false, error: Some(e), state_root })
+            (state.clone(), TxResult { success: false, error: Some(e), state_root, trace, fee_charged })
         }
     }
 }
@@ -127,25 +200,35 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         system_id: &str,
         mode: &InteractMode,
         sender: &str,
+        proof_chain: &[CapabilityToken],
+        block: u64,
+        trace: &mut Vec<TraceEvent>,
+        fee_total: &mut u128,
     ) -> Result<(), TxError> {
         let system_state = state.systems.get(system_id)
             .ok_or_else(|| TxError::SystemNotFound(system_id.to_string()))?
             .clone();
 
-        Self::check_rights(&system_state.system.rights, sender)?;
+        let ability = match mode {
+            InteractMode::TriggerAction { action } => action.as_str(),
+            InteractMode::Both { action } => action.as_str(),
+            InteractMode::EvaluateConditions => "evaluate",
+        };
+
+        Self::check_rights(&system_state, sender, proof_chain, ability, block)?;
 
         match mode {
             InteractMode::TriggerAction { action } => {
-                Self::trigger_action(state, system_id, action, sender)
+                Self::trigger_action(state, system_id, action, sender, block, trace, fee_total)
             }
 
             InteractMode::EvaluateConditions => {
-                Self::evaluate_conditions(state, system_id, sender)
+                Self::evaluate_conditions(state, system_id, sender, block, trace, fee_total)
             }
 
             InteractMode::Both { action } => {
-                Self::evaluate_conditions(state, system_id, sender)?;
-                Self::trigger_action(state, system_id, action, sender)
+                Self::evaluate_conditions(state, system_id, sender, block, trace, fee_total)?;
+                Self::trigger_action(state, system_id, action, sender, block, trace, fee_total)
             }
         }
     }
@@ -155,6 +238,9 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         system_id: &str,
         action_name: &str,
         sender: &str,
+        block: u64,
+        trace: &mut Vec<TraceEvent>,
+        fee_total: &mut u128,
     ) -> Result<(), TxError> {
         let system_state = state.systems.get(system_id)
             .ok_or_else(|| TxError::SystemNotFound(system_id.to_string()))?
@@ -163,7 +249,7 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         for condition in &system_state.system.rules.conditions {
             let action_str = format!("{:?}", condition.then).to_lowercase();
             if action_str.contains(&action_name.to_lowercase()) {
-                Self::apply_action(state, system_id, &condition.then.clone(), sender)?;
+                Self::apply_action(state, system_id, &condition.then.clone(), sender, block, trace, fee_total)?;
                 return Ok(());
             }
         }
@@ -175,92 +261,101 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         state: &mut State,
         system_id: &str,
         sender: &str,
+        block: u64,
+        trace: &mut Vec<TraceEvent>,
+        fee_total: &mut u128,
     ) -> Result<(), TxError> {
         let system_state = state.systems.get(system_id)
             .ok_or_else(|| TxError::SystemNotFound(system_id.to_string()))?
             .clone();
 
         for condition in &system_state.system.rules.conditions {
-            if Self::evaluate_expression(state, system_id, &condition.if_expr) {
-                Self::apply_action(state, system_id, &condition.then.clone(), sender)?;
+            let result = Self::evaluate_expression(state, system_id, &condition.if_expr, block)?;
+            trace.push(TraceEvent::ConditionEvaluated {
+                expr: format!("{:?}", condition.if_expr),
+                result,
+            });
+            if result {
+                Self::apply_action(state, system_id, &condition.then.clone(), sender, block, trace, fee_total)?;
             }
         }
 
         Ok(())
     }
 
+    /// Evaluates a single rule condition against `state` as of `block`. An
+    /// oracle-backed expression surfaces a missing/exhausted feed as an
+    /// error (`TxError::OracleNotFound`/`OracleStale`) rather than folding
+    /// it to `false`, so a stale feed fails loudly instead of quietly
+    /// suppressing whatever action the condition guards.
     fn evaluate_expression(
         state: &State,
         system_id: &str,
         expr: &Expression,
-    ) -> bool {
+        block: u64,
+    ) -> Result<bool, TxError> {
         match expr {
-            Expression::SwapRequested => true,
-            Expression::LiquidityAdded => true,
+            Expression::SwapRequested => Ok(true),
+            Expression::LiquidityAdded => Ok(true),
 
-            Expression::BalanceGt { value } => {
-                true // MVP: simplified, expand later
+            Expression::BalanceGt { .. } => {
+                Ok(true) // MVP: simplified, expand later
             }
 
             Expression::CollateralRatioLt { ratio } => {
-                if let Some(current) = state.get_oracle(system_id, "collateral_ratio") {
-                    current < *ratio
-                } else {
-                    false
-                }
+                Ok(state.get_oracle(system_id, "collateral_ratio", block)? < *ratio)
             }
 
             Expression::UtilizationGt { ratio } => {
-                if let Some(current) = state.get_oracle(system_id, "utilization") {
-                    current > *ratio
-                } else {
-                    false
-                }
+                Ok(state.get_oracle(system_id, "utilization", block)? > *ratio)
             }
 
             Expression::PriceGt { oracle, value } => {
-                if let Some(current) = state.get_oracle(system_id, oracle) {
-                    current > *value
-                } else {
-                    false
-                }
+                Ok(state.get_oracle(system_id, oracle, block)? > *value)
             }
 
             Expression::PriceLt { oracle, value } => {
-                if let Some(current) = state.get_oracle(system_id, oracle) {
-                    current < *value
-                } else {
-                    false
-                }
+                Ok(state.get_oracle(system_id, oracle, block)? < *value)
             }
 
             Expression::PriceEq { oracle, value } => {
-                if let Some(current) = state.get_oracle(system_id, oracle) {
-                    current == *value
-                } else {
-                    false
-                }
+                Ok(state.get_oracle(system_id, oracle, block)? == *value)
             }
 
-            Expression::TimeGt { timestamp } => {
-                true
+            Expression::PriceGtEma { oracle, value, .. } => {
+                Ok(state.get_oracle_ema(system_id, oracle, block)? > *value)
             }
 
-            Expression::HoldersCountGte { count } => {
-                true
-            }
+            Expression::TimeGt { .. } => Ok(true),
+
+            Expression::HoldersCountGte { .. } => Ok(true),
 
-            _ => false,
+            _ => Ok(false),
         }
     }
 
-    /// Apply an action to state
+    /// Apply an action to state. `system_id`/`block` feed
+    /// `State::check_circuit_breaker` for the actions that move balances
+    /// (`Transfer`/`Mint`/`Burn`/`Liquidate`/`FlashLoan`), so a rule that
+    /// would drain a system past its configured per-block limit aborts the
+    /// whole transaction instead of moving the balance. `sender` backs
+    /// `FlashLoan`, which draws `amount` out of `system_id`'s own reserve
+    /// balance, credits it to `sender`, and requires both `sender`'s
+    /// balance and `system_id`'s reserve to have been made whole again
+    /// (net of `fee`) before the transaction commits. Records a
+    /// `TraceEvent::ActionFired` plus one `TraceEvent::BalanceChanged` per
+    /// address touched.
     fn apply_action(
         state: &mut State,
-        _system_id: &str,
+        system_id: &str,
         action: &Action,
-        _sender: &str,
+        sender: &str,
+        block: u64,
+        trace: &mut Vec<TraceEvent>,
+        fee_total: &mut u128,
     ) -> Result<(), TxError> {
+        trace.push(TraceEvent::ActionFired { action: format!("{:?}", action) });
+
         match action {
             Action::Pause => {
                 Ok(())
@@ -285,23 +380,54 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
             Action::Transfer { amount, from, to } => {
                 let asset = AssetType::Eth; // MVP: default to ETH
                 let from_balance = state.get_balance(from, &asset);
+                let (fee, fee_sink) = state.compute_fee(system_id, &asset, *amount, block);
+                let total_debit = amount + fee;
 
-                if from_balance < *amount {
+                if from_balance < total_debit {
                     return Err(TxError::InsufficientBalance(
-                        format!("{}: required {}, has {}", from, amount, from_balance)
+                        format!("{}: required {}, has {}", from, total_debit, from_balance)
                     ));
                 }
 
-                state.set_balance(from, &asset, from_balance - amount);
+                state.check_circuit_breaker(system_id, &asset, -(total_debit as i128), block)?;
+
+                let asset_id = format!("{:?}", asset);
+                state.set_balance(from, &asset, from_balance - total_debit);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: from.clone(), asset: asset_id.clone(),
+                    before: from_balance, after: from_balance - total_debit,
+                });
                 let to_balance = state.get_balance(to, &asset);
                 state.set_balance(to, &asset, to_balance + amount);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: to.clone(), asset: asset_id.clone(),
+                    before: to_balance, after: to_balance + amount,
+                });
+
+                if fee > 0 {
+                    if let Some(sink) = fee_sink {
+                        let sink_balance = state.get_balance(&sink, &asset);
+                        state.set_balance(&sink, &asset, sink_balance + fee);
+                        trace.push(TraceEvent::BalanceChanged {
+                            address: sink, asset: asset_id,
+                            before: sink_balance, after: sink_balance + fee,
+                        });
+                        *fee_total += fee;
+                    }
+                }
+
                 Ok(())
             }
 
             Action::Mint { amount, to } => {
                 let asset = AssetType::Eth;
+                state.check_circuit_breaker(system_id, &asset, *amount as i128, block)?;
                 let balance = state.get_balance(to, &asset);
                 state.set_balance(to, &asset, balance + amount);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: to.clone(), asset: format!("{:?}", asset),
+                    before: balance, after: balance + amount,
+                });
                 Ok(())
             }
 
@@ -315,13 +441,80 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
                     ));
                 }
 
+                state.check_circuit_breaker(system_id, &asset, -(*amount as i128), block)?;
+
                 state.set_balance(from, &asset, balance - amount);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: from.clone(), asset: format!("{:?}", asset),
+                    before: balance, after: balance - amount,
+                });
                 Ok(())
             }
 
             Action::Liquidate { target } => {
                 // MVP: zero out target balance
-                state.set_balance(target, &AssetType::Eth, 0);
+                let asset = AssetType::Eth;
+                let balance = state.get_balance(target, &asset);
+                state.check_circuit_breaker(system_id, &asset, -(balance as i128), block)?;
+                state.set_balance(target, &asset, 0);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: target.clone(), asset: format!("{:?}", asset),
+                    before: balance, after: 0,
+                });
+                Ok(())
+            }
+
+            Action::FlashLoan { asset, amount, fee, then } => {
+                // The system itself is the reserve the loan is drawn against
+                // (the same account `Liquidate` zeroes out and circuit
+                // breakers are scoped to), so `amount` comes from and must
+                // go back to `system_id`'s own balance rather than being
+                // conjured for `sender` out of nothing.
+                let pre_balance = state.get_balance(sender, asset);
+                let reserve_balance = state.get_balance(system_id, asset);
+                if reserve_balance < *amount {
+                    return Err(TxError::InsufficientBalance(format!(
+                        "{}: flash loan reserve has {}, requested {}",
+                        system_id, reserve_balance, amount
+                    )));
+                }
+
+                state.check_circuit_breaker(system_id, asset, *amount as i128, block)?;
+
+                state.set_balance(system_id, asset, reserve_balance - amount);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: system_id.to_string(), asset: format!("{:?}", asset),
+                    before: reserve_balance, after: reserve_balance - amount,
+                });
+
+                let credited = pre_balance + amount;
+                state.set_balance(sender, asset, credited);
+                trace.push(TraceEvent::BalanceChanged {
+                    address: sender.to_string(), asset: format!("{:?}", asset),
+                    before: pre_balance, after: credited,
+                });
+
+                for nested in then {
+                    Self::apply_action(state, system_id, nested, sender, block, trace, fee_total)?;
+                }
+
+                let post_balance = state.get_balance(sender, asset);
+                let required_floor = pre_balance.saturating_sub(*fee);
+                if post_balance > required_floor {
+                    return Err(TxError::FlashLoanNotRepaid {
+                        asset: format!("{:?}", asset),
+                        shortfall: post_balance - required_floor,
+                    });
+                }
+
+                let post_reserve = state.get_balance(system_id, asset);
+                if post_reserve < reserve_balance {
+                    return Err(TxError::FlashLoanNotRepaid {
+                        asset: format!("{:?}", asset),
+                        shortfall: reserve_balance - post_reserve,
+                    });
+                }
+
                 Ok(())
             }
 
@@ -330,13 +523,19 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         }
     }
 
-    /// Execute an oracle update
+    /// Execute an oracle update, rolling the named feed's EMA track (see
+    /// `State::set_oracle`) forward using its configured `ema_period`. A
+    /// feed posted under a name not seen before is registered as a
+    /// fallback; the first feed ever observed for an oracle is its primary.
     fn execute_oracle_update(
         state: &mut State,
         system_id: &str,
         oracle_name: &str,
         value: u128,
+        source: Option<&str>,
         sender: &str,
+        block: u64,
+        trace: &mut Vec<TraceEvent>,
     ) -> Result<(), TxError> {
         let system_state = state.systems.get(system_id)
             .ok_or_else(|| TxError::SystemNotFound(system_id.to_string()))?
@@ -348,9 +547,26 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
             ));
         }
 
-        state.set_oracle(system_id, oracle_name, value)
+        let period = system_state.system.oracles.iter()
+            .find(|o| o.name == oracle_name)
+            .map(|o| o.ema_period)
+            .unwrap_or_else(crate::types::default_ema_period);
+
+        let source_name = source.unwrap_or("primary");
+        let max_staleness = crate::types::default_max_staleness();
+        let before = state.get_oracle(system_id, oracle_name, block).unwrap_or(0);
+
+        state.set_oracle(system_id, oracle_name, value, block, period, source_name, max_staleness)
             .map_err(TxError::from)?;
 
+        trace.push(TraceEvent::OracleUpdated {
+            system_id: system_id.to_string(),
+            oracle_name: oracle_name.to_string(),
+            source: source_name.to_string(),
+            before,
+            after: value,
+        });
+
         Ok(())
     }
 
@@ -361,6 +577,7 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
         asset_type: &TransactionAsset,
         amount: u128,
         sender: &str,
+        trace: &mut Vec<TraceEvent>,
     ) -> Result<(), TxError> {
         if sender != from {
             return Err(TxError::Unauthorized(
@@ -377,19 +594,73 @@ pub fn apply_tx(state: &State, tx: Transaction) -> (State, TxResult) {
             ));
         }
 
+        let asset_id = format!("{:?}", asset);
         state.set_balance(from, &asset, from_balance - amount);
+        trace.push(TraceEvent::BalanceChanged {
+            address: from.to_string(), asset: asset_id.clone(),
+            before: from_balance, after: from_balance - amount,
+        });
         let to_balance = state.get_balance(to, &asset);
         state.set_balance(to, &asset, to_balance + amount);
+        trace.push(TraceEvent::BalanceChanged {
+            address: to.to_string(), asset: asset_id,
+            before: to_balance, after: to_balance + amount,
+        });
 
         Ok(())
     }
 
+    /// Gates `ability` on `system_state`'s system for `sender`. The
+    /// deployer can always interact; otherwise `sender` (or the special
+    /// `"anyone"` entry) must be listed against `ability` in the system's
+    /// static `rights` map, or `proof_chain` must carry a UCAN-style
+    /// delegation chain (see `capability::verify_chain`) rooted at the
+    /// deployer or a static rights holder and granting `ability` on this
+    /// system to `sender`. `block` stands in for "now" when checking a
+    /// token's `exp`/`nbf`, the same way it already does for oracle
+    /// freshness elsewhere in this file.
     fn check_rights(
-        _rights: &std::collections::HashMap<String, Vec<String>>,
-        _sender: &str,
+        system_state: &SystemState,
+        sender: &str,
+        proof_chain: &[CapabilityToken],
+        ability: &str,
+        block: u64,
     ) -> Result<(), TxError> {
-        // MVP: anyone with any rights can interact
-        Ok(())
+        if sender == system_state.metadata.deployer {
+            return Ok(());
+        }
+
+        let rights = &system_state.system.rights;
+        let granted_directly = rights
+            .get("anyone")
+            .map(|abilities| abilities.iter().any(|a| a == ability))
+            .unwrap_or(false)
+            || rights
+                .get(sender)
+                .map(|abilities| abilities.iter().any(|a| a == ability))
+                .unwrap_or(false);
+
+        if granted_directly {
+            return Ok(());
+        }
+
+        if !proof_chain.is_empty() {
+            return capability::verify_chain(
+                proof_chain,
+                &system_state.metadata.deployer,
+                rights,
+                sender,
+                &system_state.system_id,
+                ability,
+                block,
+            )
+            .map_err(|e| TxError::CapabilityDenied(e.to_string()));
+        }
+
+        Err(TxError::Unauthorized(format!(
+            "{} lacks {} rights on {}",
+            sender, ability, system_state.system_id
+        )))
     }
 }
 
@@ -1,12 +1,12 @@
+use crate::backend::{JsonFileBackend, StateBackend};
 use crate::block::Block;
 use crate::log::{BlockLog, LogError, LOG_PATH};
 use crate::runtime::{Runtime, TxResult};
 use crate::state::State;
-use crate::store::{Store, StoreError, STATE_PATH};
-use crate::transaction::{Transaction, TransactionPayload};
+use crate::store::{StoreError, STATE_PATH};
+use crate::transaction::{EnvelopeError, Transaction, TransactionPayload};
 //use crate::parser::Parser;
 //use crate::hash::{compute_system_id, system_id_to_hex};
-use crate::system_registry::SystemRegistry;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -22,6 +22,9 @@ pub enum SequencerError {
 
     #[error("Registry error: {0}")]
     RegistryError(#[from] crate::system_registry::RegistryError),
+
+    #[error("Invalid transaction envelope: {0}")]
+    InvalidEnvelope(#[from] EnvelopeError),
 }
 
 #[derive(Debug)]
@@ -34,14 +37,28 @@ pub fn sequence_tx(
     tx: Transaction,
     state: &State,
 ) -> Result<(SequenceResult, State), SequencerError> {
-    sequence_tx_at(tx, state, LOG_PATH, STATE_PATH)
+    let backend = JsonFileBackend::new(STATE_PATH);
+    sequence_tx_at(tx, state, LOG_PATH, &backend)
+}
+
+/// Decode a transaction from its self-describing envelope bytes (see
+/// `Transaction::decode_envelope`) and sequence it. The entry point for
+/// transactions arriving as raw bytes rather than an already-typed
+/// `Transaction` — dispatches on the leading payload-type byte and refuses
+/// to sequence anything this build doesn't recognize.
+pub fn sequence_tx_bytes(
+    bytes: &[u8],
+    state: &State,
+) -> Result<(SequenceResult, State), SequencerError> {
+    let tx = Transaction::decode_envelope(bytes)?;
+    sequence_tx(tx, state)
 }
 
 pub fn sequence_tx_at(
     tx: Transaction,
     state: &State,
     log_path: &str,
-    state_path: &str,
+    backend: &dyn StateBackend,
 ) -> Result<(SequenceResult, State), SequencerError> {
     let latest = BlockLog::latest_from(log_path)?;
 
@@ -64,20 +81,23 @@ pub fn sequence_tx_at(
             nonce: tx.nonce,
             payload: TransactionPayload::DeploySystem {
                 system_id: system_id.clone(),
-                yaml: None, 
+                yaml: None,
             },
+            chain_id: tx.chain_id,
+            proof_chain: tx.proof_chain.clone(),
+            expected_prior_root: tx.expected_prior_root.clone(),
+            signature: tx.signature.clone(),
         }
     } else {
         tx.clone()
     };
 
-    let (new_state, tx_result) = Runtime::apply_tx(state, tx.clone());
+    let (new_state, tx_result) = Runtime::apply_tx(state, tx.clone(), next_number);
     if tx_result.success {
-        if let TransactionPayload::DeploySystem { system_id, yaml } = &tx.payload {
+        if let TransactionPayload::DeploySystem { yaml, .. } = &tx.payload {
             if let Some(yaml_content) = yaml {
                 if !yaml_content.is_empty() {
-                    let mut registry = SystemRegistry::load()?;
-                    registry.register(system_id, yaml_content)?;
+                    crate::system_registry::cached_registry().register_from_yaml(yaml_content)?;
                 }
             }
         }
@@ -87,21 +107,30 @@ pub fn sequence_tx_at(
     let block = Block::new_with_timestamp(next_number, prev_hash, vec![log_tx], state_root, timestamp);
     
     BlockLog::append_to(&block, log_path)?;
-    Store::save_to(&new_state, state_path)?;
+    backend.save(&new_state)?;
 
     Ok((SequenceResult { block, tx_result }, new_state))
 }
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::JsonFileBackend;
     use crate::log::BlockLog;
     use crate::state::State;
     use crate::store::Store;
     use crate::transaction::{Transaction, TransactionPayload, TransactionAsset};
+    use crate::signing::Keypair;
 
-    const SENDER: &str = "0x1234567890123456789012345678901234567890";
     const RECEIVER: &str = "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd";
 
+    fn sender_keypair() -> Keypair {
+        Keypair::from_seed_hex(&"11".repeat(32)).unwrap()
+    }
+
+    fn sender_address() -> String {
+        sender_keypair().address_hex()
+    }
+
  fn test_paths(test_name: &str) -> (String, String) {
     use std::time::{SystemTime, UNIX_EPOCH};
     let unique = SystemTime::now()
@@ -127,16 +156,22 @@ mod tests {
 }
 
     fn transfer_tx(nonce: u64, amount: u128) -> Transaction {
+        let sender = sender_address();
         Transaction {
-            sender: SENDER.to_string(),
+            sender: sender.clone(),
             nonce,
             payload: TransactionPayload::Transfer {
-                from: SENDER.to_string(),
+                from: sender,
                 to: RECEIVER.to_string(),
                 asset_type: TransactionAsset::Eth,
                 amount,
             },
+            chain_id: crate::signing::CHAIN_ID,
+            proof_chain: vec![],
+            expected_prior_root: None,
+            signature: String::new(),
         }
+        .sign(&sender_keypair())
     }
 
     fn sequence_tx_isolated(
@@ -145,7 +180,8 @@ mod tests {
         log_path: &str,
         state_path: &str,
     ) -> Result<(SequenceResult, State), SequencerError> {
-        sequence_tx_at(tx, state, log_path, state_path)
+        let backend = JsonFileBackend::new(state_path);
+        sequence_tx_at(tx, state, log_path, &backend)
     }
 
     #[test]
@@ -154,14 +190,14 @@ mod tests {
         let (log_path, state_path) = test_paths("seq_single");
 
         let mut state = State::new();
-        state.set_balance(SENDER, &crate::types::AssetType::Eth, 1000);
+        state.set_balance(&sender_address(), &crate::types::AssetType::Eth, 1000);
 
         let tx = transfer_tx(0, 100);
         let (result, new_state) = sequence_tx_isolated(tx, &state, &log_path, &state_path).unwrap();
 
         assert!(result.tx_result.success);
         assert_eq!(result.block.number, 1);
-        assert_eq!(new_state.get_balance(SENDER, &crate::types::AssetType::Eth), 900);
+        assert_eq!(new_state.get_balance(&sender_address(), &crate::types::AssetType::Eth), 900);
         assert_eq!(new_state.get_balance(RECEIVER, &crate::types::AssetType::Eth), 100);
 
         //cleanup("seq_single"); // COMMENT OUT FOR CLEANUP AFTER TEST RUNS
@@ -173,7 +209,7 @@ mod tests {
         let (log_path, state_path) = test_paths("seq_multi");
 
         let mut state = State::new();
-        state.set_balance(SENDER, &crate::types::AssetType::Eth, 1000);
+        state.set_balance(&sender_address(), &crate::types::AssetType::Eth, 1000);
 
         let tx1 = transfer_tx(0, 100);
         let (result1, state) = sequence_tx_isolated(tx1, &state, &log_path, &state_path).unwrap();
@@ -184,7 +220,7 @@ mod tests {
         assert_eq!(result2.block.number, 2);
 
         assert_eq!(result2.block.prev_hash, result1.block.hash);
-        assert_eq!(state.get_balance(SENDER, &crate::types::AssetType::Eth), 700);
+        assert_eq!(state.get_balance(&sender_address(), &crate::types::AssetType::Eth), 700);
         assert_eq!(state.get_balance(RECEIVER, &crate::types::AssetType::Eth), 300);
 
         //cleanup("seq_multi"); // COMMENT OUT FOR CLEANUP AFTER TEST RUNS
@@ -215,7 +251,7 @@ mod tests {
         let (log_path, state_path) = test_paths("seq_rebuild");
 
         let mut state = State::new();
-        state.set_balance(SENDER, &crate::types::AssetType::Eth, 1000);
+        state.set_balance(&sender_address(), &crate::types::AssetType::Eth, 1000);
         Store::save_to(&state, &state_path).unwrap();
 
         let tx1 = transfer_tx(0, 100);
@@ -226,7 +262,7 @@ mod tests {
 
         let rebuilt = BlockLog::rebuild_state_at(&log_path, &state_path).unwrap();
 
-        assert_eq!(rebuilt.get_balance(SENDER, &crate::types::AssetType::Eth), 700);
+        assert_eq!(rebuilt.get_balance(&sender_address(), &crate::types::AssetType::Eth), 700);
         assert_eq!(rebuilt.get_balance(RECEIVER, &crate::types::AssetType::Eth), 300);
 
         cleanup("seq_rebuild");
@@ -0,0 +1,278 @@
+//! secp256k1 transaction authentication, Ethereum-style. Unlike a detached
+//! signature that merely proves the signer *holds* the key named in
+//! `sender`, this recovers the signer's address straight from the
+//! signature (`ecrecover`-style) and requires it to equal the claimed
+//! `sender` — `sender` is never part of what's signed, only checked
+//! against what the signature proves. `Runtime::execute` rejects any
+//! transaction whose signature doesn't recover to its `sender`, or whose
+//! `chain_id` doesn't match this network's (see `CHAIN_ID`), preventing a
+//! signed transaction from one fvl network being replayed on another.
+
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use thiserror::Error;
+
+use crate::hash::keccak256;
+use crate::transaction::{Transaction, TransactionPayload};
+
+/// This network's chain id. Distinct fvl networks (testnet, staging,
+/// mainnet) should use distinct ids so a transaction signed for one can
+/// never be replayed on another.
+pub const CHAIN_ID: u64 = 1337;
+
+pub fn default_chain_id() -> u64 {
+    CHAIN_ID
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SigningError {
+    #[error("signing key seed is invalid: {0}")]
+    InvalidSeed(String),
+
+    #[error("signature is not valid hex: {0}")]
+    InvalidSignatureEncoding(String),
+
+    #[error("transaction is unsigned")]
+    Unsigned,
+
+    #[error("signature does not recover to the claimed sender")]
+    VerificationFailed,
+
+    #[error("transaction signed for chain_id {found}, this network is {expected}")]
+    WrongChain { expected: u64, found: u64 },
+}
+
+/// A secp256k1 keypair used to sign `Transaction`s.
+pub struct Keypair(SigningKey);
+
+impl Keypair {
+    pub fn generate() -> Self {
+        Keypair(SigningKey::random(&mut rand::rngs::OsRng))
+    }
+
+    /// Hex-encoded 32-byte private key, suitable for storing in config.
+    pub fn seed_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0.to_bytes()))
+    }
+
+    pub fn from_seed_hex(seed_hex: &str) -> Result<Self, SigningError> {
+        let bytes = hex::decode(seed_hex.strip_prefix("0x").unwrap_or(seed_hex))
+            .map_err(|e| SigningError::InvalidSeed(e.to_string()))?;
+        let signing_key = SigningKey::from_slice(&bytes)
+            .map_err(|e| SigningError::InvalidSeed(e.to_string()))?;
+        Ok(Keypair(signing_key))
+    }
+
+    /// The Ethereum-style address (`0x` + last 20 bytes of the keccak256 of
+    /// the uncompressed public key) transactions signed by this keypair
+    /// will use as `sender`.
+    pub fn address_hex(&self) -> String {
+        address_from_verifying_key(self.0.verifying_key())
+    }
+}
+
+fn address_from_verifying_key(verifying_key: &VerifyingKey) -> String {
+    let point = verifying_key.to_encoded_point(false);
+    let pubkey_bytes = &point.as_bytes()[1..]; // drop the 0x04 prefix
+    let hash = keccak256(pubkey_bytes);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// Signs a 32-byte prehash with `keypair`, hex-encoding the recoverable
+/// signature as `r || s || recovery_id`. Shared by anything that signs
+/// over its own canonical bytes the same Ethereum-style way `Transaction`
+/// does (see `CapabilityToken::sign` in `capability.rs`).
+pub(crate) fn sign_hash(hash: &[u8; 32], keypair: &Keypair) -> String {
+    let (signature, recovery_id): (Signature, RecoveryId) = keypair
+        .0
+        .sign_prehash_recoverable(hash)
+        .expect("signing failed");
+
+    let mut sig_bytes = Vec::with_capacity(65);
+    sig_bytes.extend_from_slice(&signature.r().to_bytes());
+    sig_bytes.extend_from_slice(&signature.s().to_bytes());
+    sig_bytes.push(recovery_id.to_byte());
+
+    format!("0x{}", hex::encode(sig_bytes))
+}
+
+/// Recovers the address that produced `signature_hex` over `hash`
+/// (`ecrecover`-style), the same recoverable-signature encoding
+/// `sign_hash` produces. Shared by anything that authenticates a signer by
+/// recovering their address rather than checking against a known public
+/// key (see `CapabilityToken::verify_signature` in `capability.rs`).
+pub(crate) fn recover_address(hash: &[u8; 32], signature_hex: &str) -> Result<String, SigningError> {
+    let sig_bytes = hex::decode(signature_hex.strip_prefix("0x").unwrap_or(signature_hex))
+        .map_err(|e| SigningError::InvalidSignatureEncoding(e.to_string()))?;
+
+    if sig_bytes.len() != 65 {
+        return Err(SigningError::InvalidSignatureEncoding(format!(
+            "expected 65 bytes (r || s || recovery_id), got {}",
+            sig_bytes.len()
+        )));
+    }
+
+    let signature = Signature::from_slice(&sig_bytes[..64])
+        .map_err(|e| SigningError::InvalidSignatureEncoding(e.to_string()))?;
+    let recovery_id = RecoveryId::from_byte(sig_bytes[64])
+        .ok_or_else(|| SigningError::InvalidSignatureEncoding("bad recovery id".to_string()))?;
+
+    let verifying_key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id)
+        .map_err(|_| SigningError::VerificationFailed)?;
+
+    Ok(address_from_verifying_key(&verifying_key))
+}
+
+impl Transaction {
+    /// The bytes a signature covers: a leading payload-type discriminant
+    /// (see `TransactionPayload::type_byte`) followed by the canonically
+    /// serialized `chain_id`, `nonce`, and `payload`. The type byte is
+    /// mixed in ahead of the JSON so that adding a new payload kind down
+    /// the line can never reinterpret or hash-collide with an existing
+    /// one's signed bytes. Deliberately excludes `sender` (which is
+    /// recovered from the signature, not an input to it) and `signature`
+    /// itself.
+    fn signing_bytes(&self) -> Vec<u8> {
+        #[derive(serde::Serialize)]
+        struct Unsigned<'a> {
+            chain_id: u64,
+            nonce: u64,
+            payload: &'a TransactionPayload,
+        }
+
+        let json = serde_json::to_vec(&Unsigned {
+            chain_id: self.chain_id,
+            nonce: self.nonce,
+            payload: &self.payload,
+        })
+        .expect("Failed to serialize transaction for signing");
+
+        let mut bytes = vec![self.payload.type_byte()];
+        bytes.extend(json);
+        bytes
+    }
+
+    /// Signs `self` with `keypair`, setting `sender` to the keypair's
+    /// derived address so the two can never diverge.
+    pub fn sign(mut self, keypair: &Keypair) -> Transaction {
+        self.sender = keypair.address_hex();
+        let hash = keccak256(&self.signing_bytes());
+        self.signature = sign_hash(&hash, keypair);
+        self
+    }
+
+    /// Recovers the signer's address from the signature and requires it to
+    /// equal `sender`, and requires `chain_id` to match this network's (see
+    /// `CHAIN_ID`). An unsigned transaction is always rejected.
+    pub fn verify_signature(&self) -> Result<(), SigningError> {
+        if self.chain_id != CHAIN_ID {
+            return Err(SigningError::WrongChain {
+                expected: CHAIN_ID,
+                found: self.chain_id,
+            });
+        }
+
+        if self.signature.is_empty() {
+            return Err(SigningError::Unsigned);
+        }
+
+        let hash = keccak256(&self.signing_bytes());
+        let recovered = recover_address(&hash, &self.signature)?;
+
+        if recovered != self.sender {
+            return Err(SigningError::VerificationFailed);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionAsset;
+
+    fn transfer(sender: &str, nonce: u64) -> Transaction {
+        Transaction {
+            sender: sender.to_string(),
+            nonce,
+            payload: TransactionPayload::Transfer {
+                from: sender.to_string(),
+                to: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                asset_type: TransactionAsset::Eth,
+                amount: 100,
+            },
+            chain_id: CHAIN_ID,
+            proof_chain: vec![],
+            expected_prior_root: None,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_sign_sets_sender_to_derived_address() {
+        let keypair = Keypair::generate();
+        let tx = transfer("placeholder", 0).sign(&keypair);
+        assert_eq!(tx.sender, keypair.address_hex());
+    }
+
+    #[test]
+    fn test_signed_transaction_verifies() {
+        let keypair = Keypair::generate();
+        let tx = transfer("placeholder", 0).sign(&keypair);
+        assert!(tx.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn test_unsigned_transaction_fails_verification() {
+        let tx = transfer("0x1234", 0);
+        assert_eq!(tx.verify_signature(), Err(SigningError::Unsigned));
+    }
+
+    #[test]
+    fn test_tampered_payload_fails_verification() {
+        let keypair = Keypair::generate();
+        let mut tx = transfer("placeholder", 0).sign(&keypair);
+        tx.nonce = 99;
+        assert_eq!(tx.verify_signature(), Err(SigningError::VerificationFailed));
+    }
+
+    #[test]
+    fn test_spoofed_sender_fails_verification() {
+        let keypair = Keypair::generate();
+        let other = Keypair::generate();
+        let mut tx = transfer("placeholder", 0).sign(&keypair);
+        tx.sender = other.address_hex();
+        assert_eq!(tx.verify_signature(), Err(SigningError::VerificationFailed));
+    }
+
+    #[test]
+    fn test_bad_signature_bytes_rejected() {
+        let keypair = Keypair::generate();
+        let mut tx = transfer("placeholder", 0).sign(&keypair);
+        tx.signature = "0xnot_hex".to_string();
+        assert!(matches!(
+            tx.verify_signature(),
+            Err(SigningError::InvalidSignatureEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_on_different_chain_id_rejected() {
+        let keypair = Keypair::generate();
+        let mut tx = transfer("placeholder", 0).sign(&keypair);
+        tx.chain_id = CHAIN_ID + 1;
+        assert_eq!(
+            tx.verify_signature(),
+            Err(SigningError::WrongChain { expected: CHAIN_ID, found: CHAIN_ID + 1 })
+        );
+    }
+
+    #[test]
+    fn test_seed_round_trip() {
+        let keypair = Keypair::generate();
+        let restored = Keypair::from_seed_hex(&keypair.seed_hex()).unwrap();
+        assert_eq!(restored.address_hex(), keypair.address_hex());
+    }
+}
@@ -0,0 +1,21 @@
+use crate::hash::keccak256;
+
+/// Domain tags keep the four logical maps (balances/oracles/nonces/systems)
+/// in disjoint key-hash spaces even though they're combined into one root.
+/// Still used to derive `Trie`'s leaf keys (see `state::balance_key_hash`
+/// and friends) even though the Sparse Merkle Tree these tags were
+/// originally defined for (`DomainTree`) has since been replaced by
+/// `trie::Trie`.
+pub const DOMAIN_BALANCE: u8 = 0;
+pub const DOMAIN_ORACLE: u8 = 1;
+pub const DOMAIN_NONCE: u8 = 2;
+pub const DOMAIN_SYSTEM: u8 = 3;
+
+/// `keccak256(domain_tag || serialized_key)` — the bit-path a key's leaf
+/// lives at, MSB-first.
+pub fn key_hash(domain_tag: u8, key_bytes: &[u8]) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(1 + key_bytes.len());
+    buf.push(domain_tag);
+    buf.extend_from_slice(key_bytes);
+    keccak256(&buf)
+}
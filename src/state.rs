@@ -1,14 +1,118 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
+use crate::block::Block;
 use crate::hash::{SystemId, keccak256, system_id_to_hex};
+use crate::smt;
+use crate::trie::{Trie, TrieNode};
 use crate::types::{FvlSystem, AssetType};
 
+/// Number of entries bundled into each snapshot chunk. Small enough that a
+/// single corrupt/missing chunk doesn't force re-fetching the whole state.
+const SNAPSHOT_CHUNK_SIZE: usize = 500;
+
+/// Fixed-point scale for EMA math (see `OracleRecord`). u128 math only, so
+/// replay stays bit-for-bit deterministic across machines.
+const EMA_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// Fixed-point scale for fee rates (see `types::FeeCurve`, `State::compute_fee`).
+/// A `base_rate`/`utilization_slope` of `FEE_RATE_SCALE` means "100%".
+pub const FEE_RATE_SCALE: u128 = 1_000_000;
+
 pub type Address = [u8; 20];
 
 pub type OracleValue = u128;
 
 pub type OracleName = String;
 
+/// One named feed backing an oracle (see `OracleRecord`): its raw
+/// last-written value, a time-weighted EMA track, and the staleness window
+/// a reader is willing to trust it within.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleSource {
+    pub name: String,
+    pub last_raw: OracleValue,
+    pub last_ema: OracleValue,
+    pub last_update_block: u64,
+    pub max_staleness: u64,
+    /// `false` until the first `observe` call on this source. Lets the
+    /// first observation initialize `last_ema` directly from its value
+    /// instead of decaying from the meaningless zero values it's seeded
+    /// with.
+    observed: bool,
+}
+
+impl OracleSource {
+    fn new(name: String, max_staleness: u64) -> Self {
+        OracleSource {
+            name,
+            last_raw: 0,
+            last_ema: 0,
+            last_update_block: 0,
+            max_staleness,
+            observed: false,
+        }
+    }
+
+    /// Rolls this source forward to `value` observed at `block`, decaying
+    /// the existing EMA by `(1 - alpha)^n` where `n` is the number of
+    /// blocks since `last_update_block` and `alpha = 2/(period+1)`, both in
+    /// `EMA_SCALE` fixed point. The very first observation just sets
+    /// `last_ema = value`.
+    fn observe(&mut self, value: OracleValue, block: u64, period: u64) {
+        if !self.observed {
+            self.last_raw = value;
+            self.last_ema = value;
+            self.last_update_block = block;
+            self.observed = true;
+            return;
+        }
+
+        let n = block.saturating_sub(self.last_update_block);
+        let alpha = 2 * EMA_SCALE / (period as u128 + 1);
+        let one_minus_alpha = EMA_SCALE - alpha;
+
+        let mut decay = EMA_SCALE;
+        for _ in 0..n {
+            decay = decay * one_minus_alpha / EMA_SCALE;
+            if decay == 0 {
+                break;
+            }
+        }
+
+        self.last_ema = (value * (EMA_SCALE - decay) + self.last_ema * decay) / EMA_SCALE;
+        self.last_raw = value;
+        self.last_update_block = block;
+    }
+
+    /// Whether this source is within its staleness window as of `current_block`.
+    fn is_fresh(&self, current_block: u64) -> bool {
+        self.observed && current_block.saturating_sub(self.last_update_block) <= self.max_staleness
+    }
+}
+
+/// An oracle's named feeds, in fallback priority order: index 0 is the
+/// primary feed (the first one ever observed), later entries are fallbacks
+/// registered by posting an update under a different source name. Reads
+/// (`State::get_oracle`/`get_oracle_ema`) return the first feed that's
+/// still within its `max_staleness` window rather than always trusting the
+/// primary, so a stale primary feed doesn't silently suppress a liquidation
+/// (see `TxError::OracleStale`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleRecord {
+    pub sources: Vec<OracleSource>,
+}
+
+impl OracleRecord {
+    fn empty() -> Self {
+        OracleRecord { sources: Vec::new() }
+    }
+
+    fn freshest(&self, current_block: u64) -> Option<&OracleSource> {
+        self.sources.iter().find(|source| source.is_fresh(current_block))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct BalanceKey {
     pub address: String,
@@ -21,6 +125,12 @@ pub struct OracleKey {
     pub oracle_name: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct CircuitBreakerKey {
+    system_id: String,
+    asset_id: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemMetadata {
     pub deployed_at: u64,
@@ -35,6 +145,17 @@ pub struct SystemState {
     pub metadata: SystemMetadata,
 }
 
+/// Per-subtree state-root cache, keyed off the dirty-sets below. `None`
+/// means "never computed yet"; present-but-dirty is handled by consulting
+/// the matching dirty-set before trusting the cached value.
+#[derive(Debug, Clone, Default)]
+struct RootCache {
+    systems: Option<[u8; 32]>,
+    balances: Option<[u8; 32]>,
+    oracles: Option<[u8; 32]>,
+    nonces: Option<[u8; 32]>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     pub systems: HashMap<String, SystemState>,
@@ -43,9 +164,35 @@ pub struct State {
     pub balances: HashMap<BalanceKey, u128>,
 
     #[serde(with = "oracle_map")]
-    pub oracles: HashMap<OracleKey, OracleValue>,
+    pub oracles: HashMap<OracleKey, OracleRecord>,
 
     pub nonces: HashMap<String, u64>,
+
+    // Net per-(system, asset) balance flow accumulated so far within
+    // `circuit_breaker_block` (see `check_circuit_breaker`). Not part of
+    // the Merkle state root: purely bookkeeping for a per-block rate limit,
+    // not consensus-relevant data. Reset whenever a transaction executes
+    // against a block number different from the one these are tracking, so
+    // replay reproduces the same resets deterministically from the block
+    // numbers already in the log, not wall-clock time.
+    #[serde(skip)]
+    circuit_breaker_block: u64,
+    #[serde(skip)]
+    circuit_breaker_flows: HashMap<CircuitBreakerKey, i128>,
+
+    // Dirty-tracking + cached per-subtree digests for `compute_state_root`.
+    // Purely a performance cache: never persisted, and cleared to "all
+    // dirty" on deserialize so a freshly loaded `State` recomputes once.
+    #[serde(skip)]
+    dirty_systems: RefCell<HashSet<String>>,
+    #[serde(skip)]
+    dirty_balances: RefCell<HashSet<BalanceKey>>,
+    #[serde(skip)]
+    dirty_oracles: RefCell<HashSet<OracleKey>>,
+    #[serde(skip)]
+    dirty_nonces: RefCell<HashSet<String>>,
+    #[serde(skip)]
+    root_cache: RefCell<RootCache>,
 }
 
 impl State {
@@ -55,12 +202,83 @@ impl State {
             balances: HashMap::new(),
             oracles: HashMap::new(),
             nonces: HashMap::new(),
+            circuit_breaker_block: 0,
+            circuit_breaker_flows: HashMap::new(),
+            dirty_systems: RefCell::new(HashSet::new()),
+            dirty_balances: RefCell::new(HashSet::new()),
+            dirty_oracles: RefCell::new(HashSet::new()),
+            dirty_nonces: RefCell::new(HashSet::new()),
+            root_cache: RefCell::new(RootCache::default()),
         }
     }
 
+    /// Combines the four domain-tagged Patricia tries (balances, oracles,
+    /// nonces, systems) into a single top-level root. A domain whose
+    /// dirty-set is empty reuses its cached root instead of rebuilding the
+    /// tree — free for a block that doesn't touch that domain at all, but a
+    /// block with even one dirty key still rebuilds that domain's tree from
+    /// every entry (`Trie::build` isn't incremental), so cost only drops to
+    /// zero, not to O(changed keys).
     pub fn compute_state_root(&self) -> [u8; 32] {
-        let serialized = self.to_canonical_bytes();
-        keccak256(&serialized)
+        let balances_root = self.balances_root_cached();
+        let oracles_root = self.oracles_root_cached();
+        let nonces_root = self.nonces_root_cached();
+        let systems_root = self.systems_root_cached();
+
+        let mut buf = Vec::with_capacity(4 * 32);
+        buf.extend_from_slice(&balances_root);
+        buf.extend_from_slice(&oracles_root);
+        buf.extend_from_slice(&nonces_root);
+        buf.extend_from_slice(&systems_root);
+        keccak256(&buf)
+    }
+
+    fn balances_root_cached(&self) -> [u8; 32] {
+        if self.dirty_balances.borrow().is_empty() {
+            if let Some(root) = self.root_cache.borrow().balances {
+                return root;
+            }
+        }
+        let root = self.balances_tree().root();
+        self.root_cache.borrow_mut().balances = Some(root);
+        self.dirty_balances.borrow_mut().clear();
+        root
+    }
+
+    fn oracles_root_cached(&self) -> [u8; 32] {
+        if self.dirty_oracles.borrow().is_empty() {
+            if let Some(root) = self.root_cache.borrow().oracles {
+                return root;
+            }
+        }
+        let root = self.oracles_tree().root();
+        self.root_cache.borrow_mut().oracles = Some(root);
+        self.dirty_oracles.borrow_mut().clear();
+        root
+    }
+
+    fn nonces_root_cached(&self) -> [u8; 32] {
+        if self.dirty_nonces.borrow().is_empty() {
+            if let Some(root) = self.root_cache.borrow().nonces {
+                return root;
+            }
+        }
+        let root = self.nonces_tree().root();
+        self.root_cache.borrow_mut().nonces = Some(root);
+        self.dirty_nonces.borrow_mut().clear();
+        root
+    }
+
+    fn systems_root_cached(&self) -> [u8; 32] {
+        if self.dirty_systems.borrow().is_empty() {
+            if let Some(root) = self.root_cache.borrow().systems {
+                return root;
+            }
+        }
+        let root = self.systems_tree().root();
+        self.root_cache.borrow_mut().systems = Some(root);
+        self.dirty_systems.borrow_mut().clear();
+        root
     }
 
     pub fn state_root_hex(&self) -> String {
@@ -68,6 +286,79 @@ impl State {
         format!("0x{}", hex::encode(root))
     }
 
+    /// Inclusion proof for a balance that exists, exclusion proof
+    /// otherwise. The returned root is the balances domain's own root, not
+    /// the combined top-level state root.
+    pub fn prove_balance(&self, address: &str, asset: &AssetType) -> ([u8; 32], Vec<TrieNode>) {
+        let key = BalanceKey {
+            address: address.to_string(),
+            asset_id: asset_to_id(asset),
+        };
+        let tree = self.balances_tree();
+        let root = tree.root();
+        let key_hash = balance_key_hash(&key);
+        (root, tree.prove(key_hash))
+    }
+
+    /// Inclusion/exclusion proof for an oracle record, against the oracles
+    /// domain's own root.
+    pub fn prove_oracle(&self, system_id: &str, oracle_name: &str) -> ([u8; 32], Vec<TrieNode>) {
+        let key = OracleKey {
+            system_id: system_id.to_string(),
+            oracle_name: oracle_name.to_string(),
+        };
+        let tree = self.oracles_tree();
+        let root = tree.root();
+        let key_hash = oracle_key_hash(&key);
+        (root, tree.prove(key_hash))
+    }
+
+    fn balances_tree(&self) -> Trie {
+        let leaves = self
+            .balances
+            .iter()
+            .map(|(key, amount)| (balance_key_hash(key), amount.to_be_bytes().to_vec()))
+            .collect();
+        Trie::build(leaves)
+    }
+
+    fn oracles_tree(&self) -> Trie {
+        let leaves = self
+            .oracles
+            .iter()
+            .map(|(key, record)| {
+                let value = serde_json::to_vec(record).expect("Failed to serialize oracle record");
+                (oracle_key_hash(key), value)
+            })
+            .collect();
+        Trie::build(leaves)
+    }
+
+    fn nonces_tree(&self) -> Trie {
+        let leaves = self
+            .nonces
+            .iter()
+            .map(|(address, nonce)| {
+                let key_hash = smt::key_hash(smt::DOMAIN_NONCE, address.as_bytes());
+                (key_hash, nonce.to_be_bytes().to_vec())
+            })
+            .collect();
+        Trie::build(leaves)
+    }
+
+    fn systems_tree(&self) -> Trie {
+        let leaves = self
+            .systems
+            .iter()
+            .map(|(system_id, system_state)| {
+                let key_hash = smt::key_hash(smt::DOMAIN_SYSTEM, system_id.as_bytes());
+                let value = serde_json::to_vec(system_state).expect("Failed to serialize system state");
+                (key_hash, value)
+            })
+            .collect();
+        Trie::build(leaves)
+    }
+
     pub fn deploy_system(
         &mut self,
         system: FvlSystem,
@@ -86,7 +377,8 @@ impl State {
                 system_id: system_id_hex.clone(),
                 oracle_name: oracle.name.clone(),
             };
-            self.oracles.insert(key, 0);
+            self.dirty_oracles.borrow_mut().insert(key.clone());
+            self.oracles.insert(key, OracleRecord::empty());
         }
 
         let system_state = SystemState {
@@ -98,6 +390,7 @@ impl State {
             },
         };
 
+        self.dirty_systems.borrow_mut().insert(system_id_hex.clone());
         self.systems.insert(system_id_hex, system_state);
         Ok(())
     }
@@ -115,33 +408,182 @@ impl State {
             address: address.to_string(),
             asset_id: asset_to_id(asset),
         };
+        self.dirty_balances.borrow_mut().insert(key.clone());
         self.balances.insert(key, amount);
     }
 
-    pub fn get_oracle(&self, system_id: &str, oracle_name: &str) -> Option<OracleValue> {
+    /// Accumulates `delta` (positive for inflow to the system, negative for
+    /// outflow) onto `system_id`'s net flow for `asset` within `block`,
+    /// resetting every system's accumulators first if `block` has moved on
+    /// from the block they were last tracking. No-op if the system has no
+    /// `CircuitBreaker` configured for `asset`. Returns
+    /// `StateError::CircuitBreakerTripped` without updating the
+    /// accumulator if applying `delta` would push the net flow past the
+    /// configured `max_delta`.
+    pub fn check_circuit_breaker(
+        &mut self,
+        system_id: &str,
+        asset: &AssetType,
+        delta: i128,
+        block: u64,
+    ) -> Result<(), StateError> {
+        let max_delta = match self.systems.get(system_id) {
+            Some(system_state) => system_state
+                .system
+                .circuit_breakers
+                .iter()
+                .find(|cb| cb.asset == *asset)
+                .and_then(|cb| match &cb.max_delta {
+                    crate::types::MaxAmount::Value { amount } => Some(*amount),
+                    crate::types::MaxAmount::None => None,
+                }),
+            None => None,
+        };
+
+        let Some(max_delta) = max_delta else {
+            return Ok(());
+        };
+
+        if self.circuit_breaker_block != block {
+            self.circuit_breaker_flows.clear();
+            self.circuit_breaker_block = block;
+        }
+
+        let key = CircuitBreakerKey {
+            system_id: system_id.to_string(),
+            asset_id: asset_to_id(asset),
+        };
+        let projected = self.circuit_breaker_flows.get(&key).copied().unwrap_or(0) + delta;
+
+        if projected.unsigned_abs() > max_delta {
+            return Err(StateError::CircuitBreakerTripped {
+                system_id: system_id.to_string(),
+                asset: key.asset_id.clone(),
+            });
+        }
+
+        self.circuit_breaker_flows.insert(key, projected);
+        Ok(())
+    }
+
+    /// Looks up `system_id`'s `FeeCurve` for `asset`, if any, and computes
+    /// the fee owed on moving `amount` of it at `block`: `base_rate` plus
+    /// `utilization_slope` scaled by the system's current `utilization`
+    /// oracle reading (a missing/stale oracle reads as zero utilization
+    /// rather than erroring, since an unconfigured or freshly-deployed
+    /// system shouldn't block transfers). Returns `(0, None)` if the system
+    /// has no fee curve for `asset`.
+    pub fn compute_fee(
+        &self,
+        system_id: &str,
+        asset: &AssetType,
+        amount: u128,
+        block: u64,
+    ) -> (u128, Option<String>) {
+        let Some(system_state) = self.systems.get(system_id) else {
+            return (0, None);
+        };
+
+        let Some(curve) = system_state.system.fees.iter().find(|f| f.asset == *asset) else {
+            return (0, None);
+        };
+
+        let utilization = self.get_oracle(system_id, "utilization", block).unwrap_or(0);
+        let rate = curve.base_rate
+            + curve.utilization_slope.saturating_mul(utilization) / FEE_RATE_SCALE;
+        let fee = amount.saturating_mul(rate) / FEE_RATE_SCALE;
+
+        (fee, Some(curve.sink.clone()))
+    }
+
+    /// The freshest feed for this oracle as of `current_block`: the first
+    /// source (in priority order) whose `max_staleness` window hasn't
+    /// elapsed. `Err(StateError::OracleNotFound)` if the oracle was never
+    /// deployed or has no feeds yet; `Err(StateError::OracleStale)` if every
+    /// registered feed has gone stale.
+    fn resolve_oracle(
+        &self,
+        system_id: &str,
+        oracle_name: &str,
+        current_block: u64,
+    ) -> Result<&OracleSource, StateError> {
         let key = OracleKey {
             system_id: system_id.to_string(),
             oracle_name: oracle_name.to_string(),
         };
-        self.oracles.get(&key).copied()
+        let record = self
+            .oracles
+            .get(&key)
+            .filter(|record| !record.sources.is_empty())
+            .ok_or_else(|| StateError::OracleNotFound(oracle_name.to_string()))?;
+
+        record
+            .freshest(current_block)
+            .ok_or_else(|| StateError::OracleStale(oracle_name.to_string()))
+    }
+
+    /// The raw last-written value of the freshest feed. See `get_oracle_ema`
+    /// for the smoothed track.
+    pub fn get_oracle(
+        &self,
+        system_id: &str,
+        oracle_name: &str,
+        current_block: u64,
+    ) -> Result<OracleValue, StateError> {
+        self.resolve_oracle(system_id, oracle_name, current_block)
+            .map(|source| source.last_raw)
     }
 
+    /// The time-weighted EMA (see `OracleSource`) of the freshest feed.
+    pub fn get_oracle_ema(
+        &self,
+        system_id: &str,
+        oracle_name: &str,
+        current_block: u64,
+    ) -> Result<OracleValue, StateError> {
+        self.resolve_oracle(system_id, oracle_name, current_block)
+            .map(|source| source.last_ema)
+    }
+
+    /// Records a new observation of `value` at `block` against the named
+    /// feed (`source_name`), decaying that feed's EMA track by the number
+    /// of blocks elapsed since its last update (see `OracleSource::observe`).
+    /// A feed not seen before is appended to the oracle's fallback order;
+    /// the first feed ever observed becomes the primary. `period` comes
+    /// from the oracle's `ema_period` config in its deployed system.
     pub fn set_oracle(
         &mut self,
         system_id: &str,
         oracle_name: &str,
         value: OracleValue,
+        block: u64,
+        period: u64,
+        source_name: &str,
+        max_staleness: u64,
     ) -> Result<(), StateError> {
         let key = OracleKey {
             system_id: system_id.to_string(),
             oracle_name: oracle_name.to_string(),
         };
 
-        if !self.oracles.contains_key(&key) {
-            return Err(StateError::OracleNotFound(oracle_name.to_string()));
+        let record = self
+            .oracles
+            .get_mut(&key)
+            .ok_or_else(|| StateError::OracleNotFound(oracle_name.to_string()))?;
+
+        match record.sources.iter_mut().find(|source| source.name == source_name) {
+            Some(source) => {
+                source.max_staleness = max_staleness;
+                source.observe(value, block, period);
+            }
+            None => {
+                let mut source = OracleSource::new(source_name.to_string(), max_staleness);
+                source.observe(value, block, period);
+                record.sources.push(source);
+            }
         }
 
-        self.oracles.insert(key, value);
+        self.dirty_oracles.borrow_mut().insert(key);
         Ok(())
     }
 
@@ -164,55 +606,228 @@ impl State {
             });
         }
 
+        self.dirty_nonces.borrow_mut().insert(address.to_string());
         self.nonces.insert(address.to_string(), current + 1);
         Ok(())
     }
 
-    fn to_canonical_bytes(&self) -> Vec<u8> {
+    /// Bundles the whole state into fixed-size, independently-verifiable
+    /// chunks plus a manifest, so a new node can fetch chunks from untrusted
+    /// peers and only has to trust the manifest's `state_root`.
+    pub fn export_snapshot(&self, block: &Block) -> Snapshot {
+        let mut entries: Vec<SnapshotEntry> = Vec::new();
+
         let mut systems: Vec<_> = self.systems.iter().collect();
         systems.sort_by_key(|(k, _)| k.as_str());
+        entries.extend(systems.into_iter().map(|(id, state)| {
+            SnapshotEntry::System(id.clone(), state.clone())
+        }));
 
         let mut balances: Vec<_> = self.balances.iter().collect();
-        balances.sort_by(|(a, _), (b, _)| {
-            a.address.cmp(&b.address)
-                .then(a.asset_id.cmp(&b.asset_id))
-        });
+        balances.sort_by(|(a, _), (b, _)| a.address.cmp(&b.address).then(a.asset_id.cmp(&b.asset_id)));
+        entries.extend(balances.into_iter().map(|(k, v)| SnapshotEntry::Balance(k.clone(), *v)));
 
         let mut oracles: Vec<_> = self.oracles.iter().collect();
-        oracles.sort_by(|(a, _), (b, _)| {
-            a.system_id.cmp(&b.system_id)
-                .then(a.oracle_name.cmp(&b.oracle_name))
-        });
+        oracles.sort_by(|(a, _), (b, _)| a.system_id.cmp(&b.system_id).then(a.oracle_name.cmp(&b.oracle_name)));
+        entries.extend(oracles.into_iter().map(|(k, v)| SnapshotEntry::Oracle(k.clone(), v.clone())));
 
         let mut nonces: Vec<_> = self.nonces.iter().collect();
         nonces.sort_by_key(|(k, _)| k.as_str());
+        entries.extend(nonces.into_iter().map(|(addr, n)| SnapshotEntry::Nonce(addr.clone(), *n)));
+
+        let mut chunks = Vec::new();
+        let mut chunk_hashes = Vec::new();
+        for chunk_entries in entries.chunks(SNAPSHOT_CHUNK_SIZE) {
+            let bytes = serde_json::to_vec(chunk_entries).expect("Failed to serialize snapshot chunk");
+            chunk_hashes.push(keccak256(&bytes));
+            chunks.push(bytes);
+        }
 
-        #[derive(Serialize)]
-        struct CanonicalState<'a> {
-            systems: Vec<(&'a String, &'a SystemState)>,
-            balances: Vec<(&'a BalanceKey, &'a u128)>,
-            oracles: Vec<(&'a OracleKey, &'a OracleValue)>,
-            nonces: Vec<(&'a String, &'a u64)>,
+        Snapshot {
+            manifest: SnapshotManifest {
+                state_root: block.state_root.clone(),
+                block_number: block.number,
+                chunk_hashes,
+            },
+            chunks,
         }
+    }
 
-        let canonical = CanonicalState {
-            systems,
-            balances,
-            oracles,
-            nonces,
-        };
+    /// Verifies every chunk against the manifest's `chunk_hashes`,
+    /// reconstructs the state, and rejects the snapshot unless the rebuilt
+    /// `compute_state_root()` matches the manifest's `state_root`.
+    pub fn import_snapshot(snapshot: Snapshot) -> Result<State, StateError> {
+        if snapshot.chunks.len() != snapshot.manifest.chunk_hashes.len() {
+            return Err(StateError::SnapshotMismatch(format!(
+                "expected {} chunks, got {}",
+                snapshot.manifest.chunk_hashes.len(),
+                snapshot.chunks.len()
+            )));
+        }
+
+        let mut state = State::new();
+        for (chunk, expected_hash) in snapshot.chunks.iter().zip(&snapshot.manifest.chunk_hashes) {
+            let actual_hash = keccak256(chunk);
+            if actual_hash != *expected_hash {
+                return Err(StateError::SnapshotMismatch(format!(
+                    "chunk hash mismatch: expected {}, got {}",
+                    hex::encode(expected_hash),
+                    hex::encode(actual_hash)
+                )));
+            }
+
+            let chunk_entries: Vec<SnapshotEntry> = serde_json::from_slice(chunk)
+                .map_err(|e| StateError::SnapshotMismatch(format!("invalid chunk contents: {}", e)))?;
+
+            for entry in chunk_entries {
+                match entry {
+                    SnapshotEntry::System(id, system_state) => {
+                        state.systems.insert(id, system_state);
+                    }
+                    SnapshotEntry::Balance(key, amount) => {
+                        state.balances.insert(key, amount);
+                    }
+                    SnapshotEntry::Oracle(key, value) => {
+                        state.oracles.insert(key, value);
+                    }
+                    SnapshotEntry::Nonce(address, nonce) => {
+                        state.nonces.insert(address, nonce);
+                    }
+                }
+            }
+        }
 
-        serde_json::to_vec(&canonical)
-            .expect("Failed to serialize state")
+        let recomputed = state.state_root_hex();
+        if recomputed != snapshot.manifest.state_root {
+            return Err(StateError::SnapshotMismatch(format!(
+                "recomputed state root {} does not match manifest root {}",
+                recomputed, snapshot.manifest.state_root
+            )));
+        }
+
+        Ok(state)
     }
 }
 
+/// One changed balance, oracle value, or newly-deployed system between two
+/// `State`s, as produced by `State::diff`. Backs `--dry-run` previews: a
+/// state-root comparison only tells a caller *that* something changed, this
+/// tells them *what*.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateChange {
+    Balance { address: String, asset: String, before: u128, after: u128 },
+    Oracle { system_id: String, oracle_name: String, before: OracleValue, after: OracleValue },
+    SystemDeployed { system_id: String },
+}
+
+impl State {
+    /// Every balance and oracle value that differs between `before` and
+    /// `after`, plus one row per system present in `after` but not
+    /// `before`. Unchanged entries are omitted. Rows are sorted by key so
+    /// the result is deterministic for display and for tests.
+    pub fn diff(before: &State, after: &State) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+
+        let mut balance_keys: Vec<&BalanceKey> =
+            before.balances.keys().chain(after.balances.keys()).collect();
+        balance_keys.sort_by(|a, b| a.address.cmp(&b.address).then(a.asset_id.cmp(&b.asset_id)));
+        balance_keys.dedup();
+        for key in balance_keys {
+            let before_amount = before.balances.get(key).copied().unwrap_or(0);
+            let after_amount = after.balances.get(key).copied().unwrap_or(0);
+            if before_amount != after_amount {
+                changes.push(StateChange::Balance {
+                    address: key.address.clone(),
+                    asset: key.asset_id.clone(),
+                    before: before_amount,
+                    after: after_amount,
+                });
+            }
+        }
+
+        let mut oracle_keys: Vec<&OracleKey> =
+            before.oracles.keys().chain(after.oracles.keys()).collect();
+        oracle_keys.sort_by(|a, b| a.system_id.cmp(&b.system_id).then(a.oracle_name.cmp(&b.oracle_name)));
+        oracle_keys.dedup();
+        for key in oracle_keys {
+            let before_value = before.oracles.get(key).and_then(primary_raw_value).unwrap_or(0);
+            let after_value = after.oracles.get(key).and_then(primary_raw_value).unwrap_or(0);
+            if before_value != after_value {
+                changes.push(StateChange::Oracle {
+                    system_id: key.system_id.clone(),
+                    oracle_name: key.oracle_name.clone(),
+                    before: before_value,
+                    after: after_value,
+                });
+            }
+        }
+
+        let mut new_systems: Vec<&String> = after
+            .systems
+            .keys()
+            .filter(|id| !before.systems.contains_key(*id))
+            .collect();
+        new_systems.sort();
+        changes.extend(
+            new_systems
+                .into_iter()
+                .map(|id| StateChange::SystemDeployed { system_id: id.clone() }),
+        );
+
+        changes
+    }
+}
+
+/// One entry from any of the four domain maps, tagged so a chunk can hold a
+/// mix of entry kinds without losing which map it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SnapshotEntry {
+    System(String, SystemState),
+    Balance(BalanceKey, u128),
+    Oracle(OracleKey, OracleRecord),
+    Nonce(String, u64),
+}
+
+/// The trusted part of a snapshot: small enough to fetch from a trusted
+/// source (a block explorer, a peer's signed header) even when the chunks
+/// themselves come from untrusted peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub state_root: String,
+    pub block_number: u64,
+    pub chunk_hashes: Vec<[u8; 32]>,
+}
+
+/// A manifest plus the chunk bytes it describes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub manifest: SnapshotManifest,
+    pub chunks: Vec<Vec<u8>>,
+}
+
 impl Default for State {
     fn default() -> Self {
         Self::new()
     }
 }
 
+fn balance_key_hash(key: &BalanceKey) -> [u8; 32] {
+    let key_bytes = serde_json::to_vec(key).expect("Failed to serialize balance key");
+    smt::key_hash(smt::DOMAIN_BALANCE, &key_bytes)
+}
+
+fn oracle_key_hash(key: &OracleKey) -> [u8; 32] {
+    let key_bytes = serde_json::to_vec(key).expect("Failed to serialize oracle key");
+    smt::key_hash(smt::DOMAIN_ORACLE, &key_bytes)
+}
+
+/// The primary (first-registered) feed's raw value, used by `State::diff`
+/// for display purposes; it ignores staleness since a dry-run diff has no
+/// "current block" of its own to check freshness against.
+fn primary_raw_value(record: &OracleRecord) -> Option<OracleValue> {
+    record.sources.first().map(|source| source.last_raw)
+}
+
 fn asset_to_id(asset: &AssetType) -> String {
     match asset {
         AssetType::Eth => "ETH".to_string(),
@@ -234,6 +849,9 @@ pub enum StateError {
     #[error("Oracle not found: {0}")]
     OracleNotFound(String),
 
+    #[error("Oracle '{0}' has no feed within its staleness window")]
+    OracleStale(String),
+
     #[error("Insufficient balance for address {address}: required {required}, has {available}")]
     InsufficientBalance {
         address: String,
@@ -250,6 +868,12 @@ pub enum StateError {
 
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+
+    #[error("Snapshot verification failed: {0}")]
+    SnapshotMismatch(String),
+
+    #[error("Circuit breaker tripped for system {system_id} asset {asset}")]
+    CircuitBreakerTripped { system_id: String, asset: String },
 }
 
 mod balance_map {
@@ -279,27 +903,27 @@ mod balance_map {
 }
 
 mod oracle_map {
-    use super::{OracleKey, OracleValue, HashMap};
+    use super::{OracleKey, OracleRecord, HashMap};
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     pub fn serialize<S>(
-        map: &HashMap<OracleKey, OracleValue>,
+        map: &HashMap<OracleKey, OracleRecord>,
         serializer: S,
     ) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let entries: Vec<(&OracleKey, &OracleValue)> = map.iter().collect();
+        let entries: Vec<(&OracleKey, &OracleRecord)> = map.iter().collect();
         entries.serialize(serializer)
     }
 
     pub fn deserialize<'de, D>(
         deserializer: D,
-    ) -> Result<HashMap<OracleKey, OracleValue>, D::Error>
+    ) -> Result<HashMap<OracleKey, OracleRecord>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let entries: Vec<(OracleKey, OracleValue)> = Vec::deserialize(deserializer)?;
+        let entries: Vec<(OracleKey, OracleRecord)> = Vec::deserialize(deserializer)?;
         Ok(entries.into_iter().collect())
     }
 }
@@ -339,6 +963,8 @@ mod tests {
                 cliffs: None,
             },
             oracles: vec![],
+            circuit_breakers: vec![],
+            fees: vec![],
         }
     }
 
@@ -424,4 +1050,224 @@ mod tests {
         let root2 = state.compute_state_root();
         assert_ne!(root1, root2);
     }
+
+    #[test]
+    fn test_prove_balance_inclusion_verifies() {
+        let mut state = State::new();
+        let address = "0x1234567890123456789012345678901234567890";
+        state.set_balance(address, &AssetType::Eth, 1000);
+
+        let (root, proof) = state.prove_balance(address, &AssetType::Eth);
+        let key_hash = balance_key_hash(&BalanceKey {
+            address: address.to_string(),
+            asset_id: asset_to_id(&AssetType::Eth),
+        });
+
+        assert!(crate::trie::verify_proof(root, key_hash, Some(&1000u128.to_be_bytes()), &proof));
+    }
+
+    #[test]
+    fn test_compute_state_root_clears_dirty_sets() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 10);
+        assert!(!state.dirty_balances.borrow().is_empty());
+
+        state.compute_state_root();
+        assert!(state.dirty_balances.borrow().is_empty());
+        assert!(state.root_cache.borrow().balances.is_some());
+    }
+
+    #[test]
+    fn test_untouched_domain_cache_survives_unrelated_update() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 10);
+        state.compute_state_root();
+        let cached_oracles_root = state.root_cache.borrow().oracles;
+
+        state.set_balance("0xabc", &AssetType::Eth, 20);
+        state.compute_state_root();
+
+        assert_eq!(state.root_cache.borrow().oracles, cached_oracles_root);
+        assert!(state.dirty_oracles.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_export_then_import_snapshot_round_trip() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 500);
+        state.set_balance("0xdef", &AssetType::Eth, 250);
+
+        let block = crate::block::Block::new(1, "0x0".to_string(), vec![], state.state_root_hex());
+        let snapshot = state.export_snapshot(&block);
+
+        let restored = State::import_snapshot(snapshot).unwrap();
+        assert_eq!(restored.get_balance("0xabc", &AssetType::Eth), 500);
+        assert_eq!(restored.get_balance("0xdef", &AssetType::Eth), 250);
+        assert_eq!(restored.state_root_hex(), state.state_root_hex());
+    }
+
+    #[test]
+    fn test_import_snapshot_rejects_tampered_chunk() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 500);
+
+        let block = crate::block::Block::new(1, "0x0".to_string(), vec![], state.state_root_hex());
+        let mut snapshot = state.export_snapshot(&block);
+        snapshot.chunks[0].push(0xff);
+
+        let result = State::import_snapshot(snapshot);
+        assert!(matches!(result, Err(StateError::SnapshotMismatch(_))));
+    }
+
+    fn system_with_oracle(period: u64) -> FvlSystem {
+        let mut system = minimal_system();
+        system.oracles = vec![Oracle {
+            name: "price".to_string(),
+            oracle_type: "price".to_string(),
+            source: "test".to_string(),
+            ema_period: period,
+        }];
+        system
+    }
+
+    #[test]
+    fn test_oracle_record_first_observation_sets_ema_directly() {
+        let mut source = OracleSource::new("primary".to_string(), 50);
+        source.observe(100, 5, 20);
+        assert_eq!(source.last_raw, 100);
+        assert_eq!(source.last_ema, 100);
+        assert_eq!(source.last_update_block, 5);
+    }
+
+    #[test]
+    fn test_oracle_record_ema_moves_toward_new_value_without_jumping_to_it() {
+        let mut source = OracleSource::new("primary".to_string(), 50);
+        source.observe(100, 1, 20);
+        source.observe(200, 2, 20);
+        assert!(source.last_ema > 100 && source.last_ema < 200);
+        assert_eq!(source.last_raw, 200);
+    }
+
+    #[test]
+    fn test_oracle_record_large_gap_converges_to_raw_value() {
+        let mut source = OracleSource::new("primary".to_string(), 50);
+        source.observe(100, 1, 20);
+        source.observe(200, 10_000, 20);
+        assert_eq!(source.last_ema, 200);
+    }
+
+    #[test]
+    fn test_set_oracle_updates_raw_and_ema() {
+        let mut state = State::new();
+        let system_id = [1u8; 32];
+        state.deploy_system(system_with_oracle(20), system_id, "0xdeployer".to_string(), 0).unwrap();
+        let system_id_hex = system_id_to_hex(&system_id);
+
+        state.set_oracle(&system_id_hex, "price", 100, 1, 20, "primary", 50).unwrap();
+        assert_eq!(state.get_oracle(&system_id_hex, "price", 1).unwrap(), 100);
+        assert_eq!(state.get_oracle_ema(&system_id_hex, "price", 1).unwrap(), 100);
+
+        state.set_oracle(&system_id_hex, "price", 200, 2, 20, "primary", 50).unwrap();
+        assert_eq!(state.get_oracle(&system_id_hex, "price", 2).unwrap(), 200);
+        let ema = state.get_oracle_ema(&system_id_hex, "price", 2).unwrap();
+        assert!(ema > 100 && ema < 200);
+    }
+
+    #[test]
+    fn test_set_oracle_unknown_oracle_errors() {
+        let mut state = State::new();
+        let system_id = [2u8; 32];
+        state.deploy_system(minimal_system(), system_id, "0xdeployer".to_string(), 0).unwrap();
+        let system_id_hex = system_id_to_hex(&system_id);
+
+        assert!(state.set_oracle(&system_id_hex, "missing", 1, 1, 20, "primary", 50).is_err());
+    }
+
+    #[test]
+    fn test_get_oracle_falls_back_to_fresh_secondary_source_when_primary_stale() {
+        let mut state = State::new();
+        let system_id = [3u8; 32];
+        state.deploy_system(system_with_oracle(20), system_id, "0xdeployer".to_string(), 0).unwrap();
+        let system_id_hex = system_id_to_hex(&system_id);
+
+        state.set_oracle(&system_id_hex, "price", 100, 0, 20, "primary", 10).unwrap();
+        state.set_oracle(&system_id_hex, "price", 150, 0, 20, "fallback", 100).unwrap();
+
+        // Primary is still fresh at block 5.
+        assert_eq!(state.get_oracle(&system_id_hex, "price", 5).unwrap(), 100);
+
+        // Primary has gone stale by block 50, fallback is still within its window.
+        assert_eq!(state.get_oracle(&system_id_hex, "price", 50).unwrap(), 150);
+    }
+
+    #[test]
+    fn test_get_oracle_errors_when_every_source_is_stale() {
+        let mut state = State::new();
+        let system_id = [4u8; 32];
+        state.deploy_system(system_with_oracle(20), system_id, "0xdeployer".to_string(), 0).unwrap();
+        let system_id_hex = system_id_to_hex(&system_id);
+
+        state.set_oracle(&system_id_hex, "price", 100, 0, 20, "primary", 10).unwrap();
+
+        let result = state.get_oracle(&system_id_hex, "price", 100);
+        assert!(matches!(result, Err(StateError::OracleStale(_))));
+    }
+
+    #[test]
+    fn test_diff_reports_only_changed_balances() {
+        let mut before = State::new();
+        before.set_balance("0xabc", &AssetType::Eth, 100);
+        before.set_balance("0xdef", &AssetType::Eth, 50);
+
+        let mut after = before.clone();
+        after.set_balance("0xabc", &AssetType::Eth, 60);
+        after.set_balance("0xnew", &AssetType::Eth, 10);
+
+        let changes = State::diff(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().any(|c| matches!(c,
+            StateChange::Balance { address, before: 100, after: 60, .. } if address == "0xabc")));
+        assert!(changes.iter().any(|c| matches!(c,
+            StateChange::Balance { address, before: 0, after: 10, .. } if address == "0xnew")));
+    }
+
+    #[test]
+    fn test_diff_is_empty_for_identical_states() {
+        let mut state = State::new();
+        state.set_balance("0xabc", &AssetType::Eth, 100);
+        let clone = state.clone();
+
+        assert!(State::diff(&state, &clone).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_newly_deployed_system() {
+        let before = State::new();
+        let mut after = before.clone();
+        after
+            .deploy_system(
+                minimal_system(),
+                [0u8; 32],
+                "0x1234567890123456789012345678901234567890".to_string(),
+                1000000,
+            )
+            .unwrap();
+
+        let changes = State::diff(&before, &after);
+        assert!(changes.iter().any(|c| matches!(c, StateChange::SystemDeployed { .. })));
+    }
+
+    #[test]
+    fn test_prove_balance_exclusion_verifies() {
+        let state = State::new();
+        let address = "0x1234567890123456789012345678901234567890";
+
+        let (root, proof) = state.prove_balance(address, &AssetType::Eth);
+        let key_hash = balance_key_hash(&BalanceKey {
+            address: address.to_string(),
+            asset_id: asset_to_id(&AssetType::Eth),
+        });
+
+        assert!(crate::trie::verify_proof(root, key_hash, None, &proof));
+    }
 }
\ No newline at end of file
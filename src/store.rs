@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::Path;
 use thiserror::Error;
+use crate::log::BlockLog;
 use crate::state::State;
 
 pub const STATE_PATH: &str = "data/state.json";
@@ -15,6 +16,9 @@ pub enum StoreError {
 
     #[error("Failed to write state file: {0}")]
     WriteError(String),
+
+    #[error("State is corrupt: expected state root {expected}, recomputed {found}")]
+    StateCorrupt { expected: String, found: String },
 }
 
 pub struct Store;
@@ -28,7 +32,15 @@ impl Store {
     pub fn save(state: &State) -> Result<(), StoreError> {
         Self::save_to(state, STATE_PATH)
     }
-    
+
+    /// `load_verified` against the default `STATE_PATH`/`log::LOG_PATH`
+    /// pair — the CLI's usual load path, so a tampered or truncated
+    /// `state.json` is caught as `StoreError::StateCorrupt` instead of
+    /// silently loaded.
+    pub fn load_verified_default() -> Result<State, StoreError> {
+        Self::load_verified(STATE_PATH, crate::log::LOG_PATH)
+    }
+
     pub fn load_from(path: &str) -> Result<State, StoreError> {
         if !Path::new(path).exists() {
             let empty = State::new();
@@ -48,16 +60,34 @@ impl Store {
         Ok(state)
     }
 
-    pub fn save_to(state: &State, path: &str) -> Result<(), StoreError> {
-        if let Some(parent) = Path::new(path).parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| StoreError::WriteError(e.to_string()))?;
+    /// Load state and verify it against the latest block's recorded
+    /// `state_root`, rather than trusting the file blindly. Returns
+    /// `StoreError::StateCorrupt` if they diverge, which indicates a
+    /// truncated or tampered `state.json`.
+    pub fn load_verified(state_path: &str, log_path: &str) -> Result<State, StoreError> {
+        let state = Self::load_from(state_path)?;
+        let found = state.state_root_hex();
+
+        let latest = BlockLog::latest_from(log_path)
+            .map_err(|e| StoreError::WriteError(e.to_string()))?;
+
+        if let Some(latest_block) = latest {
+            if latest_block.state_root != found {
+                return Err(StoreError::StateCorrupt {
+                    expected: latest_block.state_root,
+                    found,
+                });
+            }
         }
 
+        Ok(state)
+    }
+
+    pub fn save_to(state: &State, path: &str) -> Result<(), StoreError> {
         let json = serde_json::to_string_pretty(state)
             .map_err(|e| StoreError::WriteError(e.to_string()))?;
 
-        fs::write(path, json)
+        crate::fs_util::atomic_write(Path::new(path), json.as_bytes())
             .map_err(|e| StoreError::WriteError(e.to_string()))?;
 
         Ok(())
@@ -103,4 +133,58 @@ mod tests {
 
         cleanup("save_and_load");
     }
+
+    fn test_log_path(test_name: &str) -> String {
+        format!("data/test_{}/blocks.log", test_name)
+    }
+
+    #[test]
+    fn test_load_verified_detects_tampered_state() {
+        cleanup("load_verified_tampered");
+        let state_path = test_state_path("load_verified_tampered");
+        let log_path = test_log_path("load_verified_tampered");
+
+        let genesis = crate::block::Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let real_root = State::new().state_root_hex();
+        let block1 = crate::block::Block::new(1, genesis.hash.clone(), vec![], real_root);
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        // A state.json whose root doesn't match what block 1 recorded —
+        // simulating a truncated or hand-edited file.
+        let mut tampered = State::new();
+        tampered.set_balance(
+            "0x1234567890123456789012345678901234567890",
+            &crate::types::AssetType::Eth,
+            1,
+        );
+        Store::save_to(&tampered, &state_path).unwrap();
+
+        let result = Store::load_verified(&state_path, &log_path);
+        assert!(matches!(result, Err(StoreError::StateCorrupt { .. })));
+
+        cleanup("load_verified_tampered");
+    }
+
+    #[test]
+    fn test_load_verified_accepts_matching_state() {
+        cleanup("load_verified_matching");
+        let state_path = test_state_path("load_verified_matching");
+        let log_path = test_log_path("load_verified_matching");
+
+        let genesis = crate::block::Block::genesis("FVL_TESTNET");
+        BlockLog::append_to(&genesis, &log_path).unwrap();
+
+        let state = State::new();
+        let block1 = crate::block::Block::new(1, genesis.hash.clone(), vec![], state.state_root_hex());
+        BlockLog::append_to(&block1, &log_path).unwrap();
+
+        Store::save_to(&state, &state_path).unwrap();
+
+        let loaded = Store::load_verified(&state_path, &log_path).unwrap();
+        assert_eq!(loaded.state_root_hex(), state.state_root_hex());
+
+        cleanup("load_verified_matching");
+    }
 }
\ No newline at end of file
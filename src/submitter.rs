@@ -1,17 +1,32 @@
 use std::fs;
 use std::path::Path;
-use std::process::Command;
 use std::thread;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
-use crate::log::{BlockLog, LOG_PATH};
 use crate::block::Block;
+use crate::hash::keccak256;
+use crate::log::{BlockLog, LOG_PATH};
+use crate::rlp;
+use crate::rpc;
 
 const CONTRACT_PATH: &str = "data/contract.json";
 const DEFAULT_SUBMIT_INTERVAL: u64 = 5;
 const DEFAULT_POLL_INTERVAL_SECS: u64 = 10;
+const PRIVATE_KEY_ENV_VAR: &str = "FVL_SUBMITTER_PRIVATE_KEY";
+
+/// `submitStateRoot(uint256,bytes32)` selector, first 4 bytes of
+/// `keccak256("submitStateRoot(uint256,bytes32)")`.
+const SUBMIT_STATE_ROOT_SELECTOR: &[u8; 4] = &[0x64, 0x76, 0xcf, 0xa1];
+
+/// `getLatest()` selector, first 4 bytes of `keccak256("getLatest()")`.
+const GETLATEST_SELECTOR: &[u8; 4] = &[0xc3, 0x6a, 0xf4, 0x60];
 
+const DEFAULT_GAS_LIMIT: u64 = 150_000;
+const DEFAULT_PRIORITY_FEE_WEI: u64 = 1_500_000_000; // 1.5 gwei
+
+#[cfg(feature = "cast-submitter")]
 const LOCAL_PRIVATE_KEY: &str =
     "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
 
@@ -21,6 +36,16 @@ pub struct ContractConfig {
     pub deployer: String,
     pub network: String,
     pub rpc_url: String,
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+    /// Private key for submitting transactions, hex-encoded. Prefer setting
+    /// `FVL_SUBMITTER_PRIVATE_KEY` instead of committing this to disk.
+    #[serde(default)]
+    pub private_key: Option<String>,
+}
+
+fn default_chain_id() -> u64 {
+    1
 }
 
 #[derive(Error, Debug)]
@@ -37,9 +62,16 @@ pub enum SubmitterError {
     #[error("Log error: {0}")]
     LogError(#[from] crate::log::LogError),
 
+    #[cfg(feature = "cast-submitter")]
     #[error("Cast command failed: {0}")]
     CastError(String),
 
+    #[error("RPC error: {0}")]
+    RpcError(#[from] rpc::RpcError),
+
+    #[error("Signing error: {0}")]
+    SigningError(String),
+
     #[error("No blocks to submit")]
     NoBlocks,
 }
@@ -151,35 +183,136 @@ impl Submitter {
 
         let state_root_bytes = self.format_bytes32(&block.state_root)?;
 
+        #[cfg(feature = "cast-submitter")]
+        {
+            self.submit_state_root_via_cast(block, &state_root_bytes)?;
+        }
+        #[cfg(not(feature = "cast-submitter"))]
+        {
+            self.submit_state_root_via_rpc(block, &state_root_bytes)?;
+        }
+
+        self.verify_submission(block.number, &block.state_root)?;
+
+        Ok(())
+    }
+
+    /// ABI-encodes `submitStateRoot(uint256,bytes32)`, wraps it in a signed
+    /// EIP-1559 transaction, and broadcasts it with `eth_sendRawTransaction`
+    /// — no external tooling required.
+    fn submit_state_root_via_rpc(
+        &self,
+        block: &Block,
+        state_root_bytes32: &str,
+    ) -> Result<(), SubmitterError> {
+        let data = encode_submit_state_root_call(block.number, state_root_bytes32)?;
+        let signing_key = self.load_signing_key()?;
+
+        let from_address = address_from_signing_key(&signing_key);
+        let nonce = self.fetch_nonce(&from_address)?;
+        let max_fee_per_gas = self.fetch_gas_price()?;
+
+        let tx = Eip1559Transaction {
+            chain_id: self.config.chain_id,
+            nonce,
+            max_priority_fee_per_gas: DEFAULT_PRIORITY_FEE_WEI as u128,
+            max_fee_per_gas,
+            gas_limit: DEFAULT_GAS_LIMIT as u128,
+            to: decode_address(&self.config.address)?,
+            value: 0,
+            data,
+        };
+
+        let raw = tx.sign_and_encode(&signing_key);
+        let raw_hex = format!("0x{}", hex::encode(raw));
+
+        let tx_hash = rpc::call(&self.config.rpc_url, "eth_sendRawTransaction", json!([raw_hex]))?;
+        let tx_hash = tx_hash.as_str().unwrap_or("unknown").to_string();
+        println!("Transaction hash: {}", tx_hash);
+
+        self.wait_for_receipt(&tx_hash)?;
+        Ok(())
+    }
+
+    fn wait_for_receipt(&self, tx_hash: &str) -> Result<(), SubmitterError> {
+        for _ in 0..30 {
+            let receipt = rpc::call(
+                &self.config.rpc_url,
+                "eth_getTransactionReceipt",
+                json!([tx_hash]),
+            )?;
+            if !receipt.is_null() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_secs(2));
+        }
+        Err(SubmitterError::RpcError(rpc::RpcError::CallFailed(
+            format!("transaction {} was never included", tx_hash),
+        )))
+    }
+
+    fn fetch_nonce(&self, address: &str) -> Result<u64, SubmitterError> {
+        let result = rpc::call(
+            &self.config.rpc_url,
+            "eth_getTransactionCount",
+            json!([address, "pending"]),
+        )?;
+        parse_hex_quantity(&result)
+    }
+
+    fn fetch_gas_price(&self) -> Result<u128, SubmitterError> {
+        let result = rpc::call(&self.config.rpc_url, "eth_gasPrice", json!([]))?;
+        Ok(parse_hex_quantity(&result)? as u128)
+    }
+
+    fn load_signing_key(&self) -> Result<k256::ecdsa::SigningKey, SubmitterError> {
+        let hex_key = std::env::var(PRIVATE_KEY_ENV_VAR)
+            .ok()
+            .or_else(|| self.config.private_key.clone())
+            .ok_or_else(|| {
+                SubmitterError::SigningError(format!(
+                    "no private key configured; set {} or contract.json's private_key",
+                    PRIVATE_KEY_ENV_VAR
+                ))
+            })?;
+
+        let bytes = hex::decode(hex_key.strip_prefix("0x").unwrap_or(&hex_key))
+            .map_err(|e| SubmitterError::SigningError(e.to_string()))?;
+
+        k256::ecdsa::SigningKey::from_slice(&bytes)
+            .map_err(|e| SubmitterError::SigningError(e.to_string()))
+    }
+
+    #[cfg(feature = "cast-submitter")]
+    fn submit_state_root_via_cast(
+        &self,
+        block: &Block,
+        state_root_bytes32: &str,
+    ) -> Result<(), SubmitterError> {
+        use std::process::Command;
+
         let output = Command::new("cast")
             .args([
                 "send",
                 &self.config.address,
                 "submitStateRoot(uint256,bytes32)",
                 &block.number.to_string(),
-                &state_root_bytes,
+                state_root_bytes32,
                 "--rpc-url",
                 &self.config.rpc_url,
                 "--private-key",
                 LOCAL_PRIVATE_KEY,
             ])
             .output()
-            .map_err(|e| SubmitterError::CastError(
-                format!("Failed to run cast: {}", e)
-            ))?;
+            .map_err(|e| SubmitterError::CastError(format!("Failed to run cast: {}", e)))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(SubmitterError::CastError(
-                format!("cast send failed: {}", stderr)
-            ));
+            return Err(SubmitterError::CastError(format!("cast send failed: {}", stderr)));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         println!("Transaction hash: {}", Self::extract_tx_hash(&stdout));
-
-        self.verify_submission(block.number, &block.state_root)?;
-
         Ok(())
     }
 
@@ -188,6 +321,30 @@ impl Submitter {
         _block_number: u64,
         _expected_root: &str,
     ) -> Result<(), SubmitterError> {
+        #[cfg(feature = "cast-submitter")]
+        {
+            self.verify_submission_via_cast()
+        }
+        #[cfg(not(feature = "cast-submitter"))]
+        {
+            self.verify_submission_via_rpc()
+        }
+    }
+
+    fn verify_submission_via_rpc(&self) -> Result<(), SubmitterError> {
+        let call_params = json!({
+            "to": self.config.address,
+            "data": format!("0x{}", hex::encode(GETLATEST_SELECTOR)),
+        });
+        let result = rpc::call(&self.config.rpc_url, "eth_call", json!([call_params, "latest"]))?;
+        println!("On-chain state:    {}", result);
+        Ok(())
+    }
+
+    #[cfg(feature = "cast-submitter")]
+    fn verify_submission_via_cast(&self) -> Result<(), SubmitterError> {
+        use std::process::Command;
+
         let output = Command::new("cast")
             .args([
                 "call",
@@ -197,9 +354,7 @@ impl Submitter {
                 &self.config.rpc_url,
             ])
             .output()
-            .map_err(|e| SubmitterError::CastError(
-                format!("Failed to run cast call: {}", e)
-            ))?;
+            .map_err(|e| SubmitterError::CastError(format!("Failed to run cast call: {}", e)))?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -213,7 +368,7 @@ impl Submitter {
         let stripped = hex_str.strip_prefix("0x").unwrap_or(hex_str);
 
         if stripped.len() > 64 {
-            return Err(SubmitterError::CastError(
+            return Err(SubmitterError::SigningError(
                 format!("State root too long: {}", stripped.len())
             ));
         }
@@ -222,6 +377,7 @@ impl Submitter {
         Ok(padded)
     }
 
+    #[cfg(feature = "cast-submitter")]
     fn extract_tx_hash(output: &str) -> &str {
         for line in output.lines() {
             if line.starts_with("transactionHash") {
@@ -232,24 +388,130 @@ impl Submitter {
     }
 }
 
+fn decode_address(address: &str) -> Result<[u8; 20], SubmitterError> {
+    let bytes = hex::decode(address.strip_prefix("0x").unwrap_or(address))
+        .map_err(|e| SubmitterError::SigningError(e.to_string()))?;
+    bytes
+        .try_into()
+        .map_err(|_| SubmitterError::SigningError(format!("not a 20-byte address: {}", address)))
+}
+
+fn parse_hex_quantity(value: &serde_json::Value) -> Result<u64, SubmitterError> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| SubmitterError::SigningError("expected hex-string RPC result".to_string()))?;
+    u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16)
+        .map_err(|e| SubmitterError::SigningError(e.to_string()))
+}
+
+/// `keccak256("submitStateRoot(uint256,bytes32)")[..4] || block_number (32B BE) || state_root (32B)`.
+fn encode_submit_state_root_call(
+    block_number: u64,
+    state_root_bytes32: &str,
+) -> Result<Vec<u8>, SubmitterError> {
+    let state_root = hex::decode(state_root_bytes32.strip_prefix("0x").unwrap_or(state_root_bytes32))
+        .map_err(|e| SubmitterError::SigningError(e.to_string()))?;
+
+    let mut data = Vec::with_capacity(4 + 32 + 32);
+    data.extend_from_slice(SUBMIT_STATE_ROOT_SELECTOR);
+    data.extend_from_slice(&[0u8; 24]);
+    data.extend_from_slice(&block_number.to_be_bytes());
+    data.extend_from_slice(&state_root);
+    Ok(data)
+}
+
+fn address_from_signing_key(signing_key: &k256::ecdsa::SigningKey) -> String {
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let verifying_key = signing_key.verifying_key();
+    let point = verifying_key.to_encoded_point(false);
+    let pubkey_bytes = &point.as_bytes()[1..]; // drop the 0x04 prefix
+    let hash = keccak256(pubkey_bytes);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// An EIP-1559 (type-2) transaction, RLP-encoded and signed the way every
+/// post-London Ethereum client expects.
+struct Eip1559Transaction {
+    chain_id: u64,
+    nonce: u64,
+    max_priority_fee_per_gas: u128,
+    max_fee_per_gas: u128,
+    gas_limit: u128,
+    to: [u8; 20],
+    value: u128,
+    data: Vec<u8>,
+}
+
+impl Eip1559Transaction {
+    fn unsigned_fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp::encode_uint(self.chain_id as u128),
+            rlp::encode_uint(self.nonce as u128),
+            rlp::encode_uint(self.max_priority_fee_per_gas),
+            rlp::encode_uint(self.max_fee_per_gas),
+            rlp::encode_uint(self.gas_limit),
+            rlp::encode_bytes(&self.to),
+            rlp::encode_uint(self.value),
+            rlp::encode_bytes(&self.data),
+            rlp::encode_list(&[]), // empty access list
+        ]
+    }
+
+    fn signing_hash(&self) -> [u8; 32] {
+        let mut payload = vec![0x02u8];
+        payload.extend_from_slice(&rlp::encode_list(&self.unsigned_fields()));
+        keccak256(&payload)
+    }
+
+    fn sign_and_encode(&self, signing_key: &k256::ecdsa::SigningKey) -> Vec<u8> {
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+        let hash = self.signing_hash();
+        let (signature, recovery_id): (k256::ecdsa::Signature, k256::ecdsa::RecoveryId) =
+            signing_key.sign_prehash_recoverable(&hash).expect("signing failed");
+
+        let r = signature.r().to_bytes();
+        let s = signature.s().to_bytes();
+
+        let mut fields = self.unsigned_fields();
+        fields.push(rlp::encode_uint(recovery_id.to_byte() as u128));
+        fields.push(rlp::encode_bytes(&r));
+        fields.push(rlp::encode_bytes(&s));
+
+        let mut raw = vec![0x02u8];
+        raw.extend_from_slice(&rlp::encode_list(&fields));
+        raw
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_format_bytes32_short() {
-        let submitter = Submitter {
-            config: ContractConfig {
-                address: "0x0".to_string(),
-                deployer: "0x0".to_string(),
-                network: "local".to_string(),
-                rpc_url: "http://localhost:8545".to_string(),
-            },
+    fn test_config() -> ContractConfig {
+        ContractConfig {
+            address: "0x0000000000000000000000000000000000000000".to_string(),
+            deployer: "0x0".to_string(),
+            network: "local".to_string(),
+            rpc_url: "http://localhost:8545".to_string(),
+            chain_id: 31337,
+            private_key: None,
+        }
+    }
+
+    fn test_submitter() -> Submitter {
+        Submitter {
+            config: test_config(),
             submit_interval: 5,
             poll_interval_secs: 10,
             last_submitted_block: 0,
-        };
+        }
+    }
 
+    #[test]
+    fn test_format_bytes32_short() {
+        let submitter = test_submitter();
         let result = submitter.format_bytes32("0xabc").unwrap();
         assert_eq!(result.len(), 66);
         assert!(result.starts_with("0x"));
@@ -257,21 +519,55 @@ mod tests {
 
     #[test]
     fn test_format_bytes32_full() {
-        let submitter = Submitter {
-            config: ContractConfig {
-                address: "0x0".to_string(),
-                deployer: "0x0".to_string(),
-                network: "local".to_string(),
-                rpc_url: "http://localhost:8545".to_string(),
-            },
-            submit_interval: 5,
-            poll_interval_secs: 10,
-            last_submitted_block: 0,
-        };
-
+        let submitter = test_submitter();
         let full_root = "0xdf6f2425d678d5449329048dd175444cf8f051ae4510c758f901c6c2258255da";
         let result = submitter.format_bytes32(full_root).unwrap();
         assert_eq!(result.len(), 66);
         assert!(result.starts_with("0x"));
     }
+
+    #[test]
+    fn test_selectors_match_keccak_of_signature() {
+        let submit = keccak256(b"submitStateRoot(uint256,bytes32)");
+        assert_eq!(&submit[..4], SUBMIT_STATE_ROOT_SELECTOR);
+
+        let get_latest = keccak256(b"getLatest()");
+        assert_eq!(&get_latest[..4], GETLATEST_SELECTOR);
+    }
+
+    #[test]
+    fn test_encode_submit_state_root_call_layout() {
+        let state_root = "0x000000000000000000000000000000000000000000000000000000000000002a";
+        let data = encode_submit_state_root_call(7, state_root).unwrap();
+
+        assert_eq!(&data[..4], SUBMIT_STATE_ROOT_SELECTOR);
+        assert_eq!(data.len(), 4 + 32 + 32);
+        assert_eq!(data[4 + 31], 7);
+        assert_eq!(data[data.len() - 1], 0x2a);
+    }
+
+    #[test]
+    fn test_signed_transaction_starts_with_type_2_byte() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let tx = Eip1559Transaction {
+            chain_id: 31337,
+            nonce: 0,
+            max_priority_fee_per_gas: 1_500_000_000,
+            max_fee_per_gas: 20_000_000_000,
+            gas_limit: 150_000,
+            to: [0x11; 20],
+            value: 0,
+            data: vec![0xde, 0xad, 0xbe, 0xef],
+        };
+
+        let raw = tx.sign_and_encode(&signing_key);
+        assert_eq!(raw[0], 0x02);
+    }
+
+    #[test]
+    fn test_address_from_signing_key_is_twenty_bytes() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let address = address_from_signing_key(&signing_key);
+        assert_eq!(address.len(), 42); // "0x" + 40 hex chars
+    }
 }
@@ -1,9 +1,14 @@
 use std::fs;
 use std::path::Path;
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::cache::{CacheMetrics, CacheMetricsSnapshot, LruCache};
+use crate::hash::{compute_system_id, system_id_to_hex};
+use crate::parser::Parser;
+
 const REGISTRY_PATH: &str = "data/systems.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +30,9 @@ pub enum RegistryError {
 
     #[error("System not found in registry: {0}")]
     SystemNotFound(String),
+
+    #[error("YAML does not parse into a valid system: {0}")]
+    InvalidSystem(String),
 }
 
 impl SystemRegistry {
@@ -78,6 +86,21 @@ impl SystemRegistry {
         self.save()
     }
 
+    /// Parse and canonicalize `yaml`, derive its content hash, and store it
+    /// keyed by that hash. Unlike `register`, the caller never supplies the
+    /// id: two byte-different-but-equivalent YAML documents (e.g. differing
+    /// only in whitespace or field order) parse to the same `FvlSystem` and
+    /// so always collide on the same id. Returns the computed system_id (hex).
+    pub fn register_from_yaml(&mut self, yaml: &str) -> Result<String, RegistryError> {
+        let system = Parser::parse_yaml(yaml)
+            .map_err(|e| RegistryError::InvalidSystem(e.to_string()))?;
+        let system_id = system_id_to_hex(
+            &compute_system_id(&system).map_err(|e| RegistryError::InvalidSystem(e.to_string()))?,
+        );
+        self.register(&system_id, yaml)?;
+        Ok(system_id)
+    }
+
     /// Retrieve YAML for a system
     pub fn get(&self, system_id: &str) -> Option<&String> {
         self.systems.get(system_id)
@@ -87,6 +110,25 @@ impl SystemRegistry {
     pub fn contains(&self, system_id: &str) -> bool {
         self.systems.contains_key(system_id)
     }
+
+    /// Re-derive the content hash of every stored entry from its YAML and
+    /// compare it against the key it's stored under. Returns the ids of any
+    /// entries whose stored YAML no longer hashes to its key — tampering,
+    /// corruption, or an entry written via the legacy caller-supplied-id
+    /// `register` path with a mismatched id.
+    pub fn verify_integrity(&self) -> Vec<String> {
+        self.systems
+            .iter()
+            .filter(|(system_id, yaml)| {
+                let recomputed_id = Parser::parse_yaml(yaml)
+                    .ok()
+                    .and_then(|system| compute_system_id(&system).ok())
+                    .map(|id| system_id_to_hex(&id));
+                recomputed_id.as_deref() != Some(system_id.as_str())
+            })
+            .map(|(system_id, _)| system_id.clone())
+            .collect()
+    }
 }
 
 impl Default for SystemRegistry {
@@ -95,6 +137,74 @@ impl Default for SystemRegistry {
     }
 }
 
+/// LRU-cached read layer in front of `SystemRegistry`, keyed by `system_id`.
+/// The sequencer reloads `SystemRegistry::load()` from disk on every
+/// `DeploySystem` tx; this keeps hot lookups in memory while still falling
+/// through to disk (and promoting into the cache) on a miss.
+pub struct CachedSystemRegistry {
+    cache: Mutex<LruCache<String, String>>,
+    metrics: CacheMetrics,
+}
+
+impl CachedSystemRegistry {
+    pub fn with_capacity(capacity: usize) -> Self {
+        CachedSystemRegistry {
+            cache: Mutex::new(LruCache::new(capacity)),
+            metrics: CacheMetrics::default(),
+        }
+    }
+
+    /// Look up a system's YAML by id, serving from cache when possible.
+    pub fn get(&self, system_id: &str) -> Result<Option<String>, RegistryError> {
+        if let Some(yaml) = self.cache.lock().unwrap().get(&system_id.to_string()) {
+            self.metrics.record_hit();
+            return Ok(Some(yaml.clone()));
+        }
+
+        self.metrics.record_miss();
+        let registry = SystemRegistry::load()?;
+        let yaml = registry.get(system_id).cloned();
+
+        if let Some(yaml) = &yaml {
+            self.cache.lock().unwrap().put(system_id.to_string(), yaml.clone());
+        }
+
+        Ok(yaml)
+    }
+
+    /// Register a system, writing through to disk and populating the cache.
+    pub fn register(&self, system_id: &str, yaml: &str) -> Result<(), RegistryError> {
+        let mut registry = SystemRegistry::load()?;
+        registry.register(system_id, yaml)?;
+        self.cache.lock().unwrap().put(system_id.to_string(), yaml.to_string());
+        Ok(())
+    }
+
+    /// Parse, canonicalize, and hash `yaml`, writing it through to disk
+    /// keyed by its content hash and populating the cache. Returns the
+    /// computed system_id (hex).
+    pub fn register_from_yaml(&self, yaml: &str) -> Result<String, RegistryError> {
+        let mut registry = SystemRegistry::load()?;
+        let system_id = registry.register_from_yaml(yaml)?;
+        self.cache.lock().unwrap().put(system_id.clone(), yaml.to_string());
+        Ok(system_id)
+    }
+
+    pub fn metrics(&self) -> CacheMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+}
+
+static CACHED_REGISTRY: OnceLock<CachedSystemRegistry> = OnceLock::new();
+
+/// Process-wide cached registry, sized from `CliConfig::registry_cache_capacity`.
+pub fn cached_registry() -> &'static CachedSystemRegistry {
+    CACHED_REGISTRY.get_or_init(|| {
+        let capacity = crate::cli::config::CliConfig::load_or_default().registry_cache_capacity;
+        CachedSystemRegistry::with_capacity(capacity)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,4 +221,89 @@ mod tests {
         assert!(registry.contains("0xabc123"));
         assert!(registry.get("0xabc123").is_some());
     }
+
+    #[test]
+    fn test_cached_registry_records_hits_and_misses() {
+        let cached = CachedSystemRegistry::with_capacity(4);
+        cached.register("0xcache1", "system: Cached").unwrap();
+
+        // Served from the write-through cache: a hit, no disk read needed.
+        assert_eq!(cached.get("0xcache1").unwrap().as_deref(), Some("system: Cached"));
+
+        // Unknown id: miss, falls through to disk, finds nothing.
+        assert!(cached.get("0xmissing").unwrap().is_none());
+
+        let metrics = cached.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 1);
+    }
+
+    const MINIMAL_YAML: &str = r#"
+system: "Test"
+pool:
+  collect:
+    from:
+      type: anyone
+    what:
+      type: eth
+    min:
+      type: zero
+    max:
+      type: none
+    cap:
+      type: none
+rules:
+  conditions: []
+  distribute:
+    formula:
+      type: proportional
+    to:
+      type: contributors
+    triggers: manual
+rights: {}
+time:
+  start:
+    type: now
+  end:
+    type: none
+  locks:
+    type: none
+  vesting:
+    type: none
+oracles: []
+"#;
+
+    // Same system, differing only in incidental whitespace.
+    const REFORMATTED_YAML: &str = "system: \"Test\"\npool:\n  collect:\n    from:\n      type: anyone\n    what:\n      type: eth\n    min:\n      type: zero\n    max:\n      type: none\n    cap:\n      type: none\nrules:\n  conditions: []\n  distribute:\n    formula:\n      type: proportional\n    to:\n      type: contributors\n    triggers: manual\nrights: {}\ntime:\n  start:\n    type: now\n  end:\n    type: none\n  locks:\n    type: none\n  vesting:\n    type: none\noracles: []\n";
+
+    #[test]
+    fn test_register_from_yaml_is_content_addressed() {
+        let mut registry = SystemRegistry::new();
+
+        let id1 = registry.register_from_yaml(MINIMAL_YAML).unwrap();
+        let id2 = registry.register_from_yaml(REFORMATTED_YAML).unwrap();
+
+        assert_eq!(id1, id2);
+        assert!(registry.contains(&id1));
+    }
+
+    #[test]
+    fn test_register_from_yaml_rejects_invalid_system() {
+        let mut registry = SystemRegistry::new();
+        let result = registry.register_from_yaml("not: a valid system");
+        assert!(matches!(result, Err(RegistryError::InvalidSystem(_))));
+    }
+
+    #[test]
+    fn test_verify_integrity_flags_mismatched_entry() {
+        let mut registry = SystemRegistry::new();
+        let real_id = registry.register_from_yaml(MINIMAL_YAML).unwrap();
+
+        // Written directly under the wrong key, bypassing the content-hash path.
+        registry.register("0xnot-the-real-hash", MINIMAL_YAML).unwrap();
+
+        let tampered = registry.verify_integrity();
+        assert!(tampered.contains(&"0xnot-the-real-hash".to_string()));
+        assert!(!tampered.contains(&real_id));
+    }
 }
\ No newline at end of file
@@ -1,11 +1,37 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use crate::types::u128_as_string;
+use crate::capability::CapabilityToken;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub sender: String,
     pub nonce: u64,
     pub payload: TransactionPayload,
+    /// The fvl network this transaction was signed for. Mixed into the
+    /// signed preimage (EIP-155-style) so a signature can't be replayed on
+    /// a different network. See `crate::signing::CHAIN_ID`.
+    #[serde(default = "crate::signing::default_chain_id")]
+    pub chain_id: u64,
+    /// Delegation chain proving `sender` holds an `AccessRule::Capability`
+    /// grant, root-to-leaf. Empty unless the target system gates on one.
+    #[serde(default)]
+    pub proof_chain: Vec<CapabilityToken>,
+    /// If set, `Runtime::execute` refuses this transaction unless
+    /// `state.state_root_hex()` still equals it at the moment of execution
+    /// (see `TxError::StatePrecondition`). Lets a script that builds and
+    /// submits a batch against a known snapshot guarantee each step only
+    /// applies if nothing else landed in between, instead of silently
+    /// running against a chain that's since moved on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_prior_root: Option<String>,
+    /// Recoverable secp256k1 signature (hex-encoded `r || s || recovery_id`,
+    /// 65 bytes) over `{chain_id, nonce, payload}`. `sender` is never part
+    /// of the signed preimage: it's recovered from the signature itself and
+    /// must equal the claimed `sender`, the same way Ethereum derives an
+    /// address from `ecrecover`. See `crate::signing`.
+    #[serde(default)]
+    pub signature: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +51,13 @@ pub enum TransactionPayload {
         oracle_name: String,
         #[serde(with = "u128_as_string")]
         value: u128,
+        /// Which named feed this update is for (see `state::OracleSource`).
+        /// `None` registers/updates the default `"primary"` feed; a
+        /// deployer posting under a different name registers a fallback
+        /// that `State::get_oracle`/`get_oracle_ema` only fall back to once
+        /// the primary goes stale.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
     },
     Transfer {
         from: String,
@@ -34,6 +67,79 @@ pub enum TransactionPayload {
         amount: u128,
     },
 }
+
+impl TransactionPayload {
+    /// Stable one-byte discriminant, independent of serde's `"type"` string
+    /// tag. Mixed into the signing preimage (see `Transaction::signing_bytes`)
+    /// so a future payload kind can never reinterpret or hash-collide with
+    /// an existing one, and checked by `Transaction::decode_envelope` so a
+    /// byte this build doesn't recognize is rejected outright instead of
+    /// falling through to a best-effort JSON parse. New variants must pick
+    /// an unused byte here and never reassign an existing one.
+    pub fn type_byte(&self) -> u8 {
+        match self {
+            TransactionPayload::DeploySystem { .. } => 0x01,
+            TransactionPayload::Interact { .. } => 0x02,
+            TransactionPayload::OracleUpdate { .. } => 0x03,
+            TransactionPayload::Transfer { .. } => 0x04,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    #[error("envelope is empty")]
+    Empty,
+
+    #[error("unknown transaction payload type byte: 0x{0:02x}")]
+    UnknownPayloadType(u8),
+
+    #[error("envelope declares type byte 0x{declared:02x} but decodes to payload type 0x{actual:02x}")]
+    TypeMismatch { declared: u8, actual: u8 },
+
+    #[error("failed to decode transaction JSON: {0}")]
+    Json(String),
+}
+
+/// Every payload type byte this build understands. `decode_envelope` refuses
+/// anything outside this set rather than guessing at its shape.
+const KNOWN_PAYLOAD_TYPES: &[u8] = &[0x01, 0x02, 0x03, 0x04];
+
+impl Transaction {
+    /// Self-describing wire encoding: a leading one-byte payload-type
+    /// discriminant (see `TransactionPayload::type_byte`) followed by the
+    /// transaction's JSON encoding. Meant for contexts (future RPC ingestion,
+    /// strict-mode replay) that need to recognize and reject an unsupported
+    /// payload kind before attempting to parse it.
+    pub fn encode_envelope(&self) -> Vec<u8> {
+        let mut bytes = vec![self.payload.type_byte()];
+        bytes.extend(serde_json::to_vec(self).expect("Failed to serialize transaction"));
+        bytes
+    }
+
+    /// Inverse of `encode_envelope`. Rejects an empty buffer, a leading byte
+    /// outside `KNOWN_PAYLOAD_TYPES`, and a leading byte that doesn't match
+    /// the type of the payload it actually decodes to (a tampered or
+    /// mis-tagged envelope).
+    pub fn decode_envelope(bytes: &[u8]) -> Result<Transaction, EnvelopeError> {
+        let (&declared_type, json) = bytes.split_first().ok_or(EnvelopeError::Empty)?;
+
+        if !KNOWN_PAYLOAD_TYPES.contains(&declared_type) {
+            return Err(EnvelopeError::UnknownPayloadType(declared_type));
+        }
+
+        let tx: Transaction =
+            serde_json::from_slice(json).map_err(|e| EnvelopeError::Json(e.to_string()))?;
+
+        let actual_type = tx.payload.type_byte();
+        if actual_type != declared_type {
+            return Err(EnvelopeError::TypeMismatch { declared: declared_type, actual: actual_type });
+        }
+
+        Ok(tx)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum InteractMode {
@@ -53,4 +159,83 @@ pub enum TransactionAsset {
         #[serde(with = "u128_as_string")]
         id: u128,
     },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_tx() -> Transaction {
+        Transaction {
+            sender: "0x1234567890123456789012345678901234567890".to_string(),
+            nonce: 0,
+            payload: TransactionPayload::Transfer {
+                from: "0x1234567890123456789012345678901234567890".to_string(),
+                to: "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd".to_string(),
+                asset_type: TransactionAsset::Eth,
+                amount: 100,
+            },
+            chain_id: crate::signing::CHAIN_ID,
+            proof_chain: vec![],
+            expected_prior_root: None,
+            signature: String::new(),
+        }
+    }
+
+    fn deploy_tx() -> Transaction {
+        Transaction {
+            sender: "0x1234567890123456789012345678901234567890".to_string(),
+            nonce: 0,
+            payload: TransactionPayload::DeploySystem {
+                system_id: "0xdead".to_string(),
+                yaml: None,
+            },
+            chain_id: crate::signing::CHAIN_ID,
+            proof_chain: vec![],
+            expected_prior_root: None,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_type_byte_is_stable_per_variant() {
+        assert_eq!(deploy_tx().payload.type_byte(), 0x01);
+        assert_eq!(transfer_tx().payload.type_byte(), 0x04);
+    }
+
+    #[test]
+    fn test_envelope_round_trips_known_types() {
+        for tx in [transfer_tx(), deploy_tx()] {
+            let encoded = tx.encode_envelope();
+            let decoded = Transaction::decode_envelope(&encoded).unwrap();
+            assert_eq!(decoded.payload.type_byte(), tx.payload.type_byte());
+            assert_eq!(decoded.nonce, tx.nonce);
+        }
+    }
+
+    #[test]
+    fn test_decode_envelope_rejects_unknown_type_byte() {
+        let mut encoded = transfer_tx().encode_envelope();
+        encoded[0] = 0xff;
+        assert_eq!(
+            Transaction::decode_envelope(&encoded),
+            Err(EnvelopeError::UnknownPayloadType(0xff))
+        );
+    }
+
+    #[test]
+    fn test_decode_envelope_rejects_empty_buffer() {
+        assert_eq!(Transaction::decode_envelope(&[]), Err(EnvelopeError::Empty));
+    }
+
+    #[test]
+    fn test_decode_envelope_rejects_mismatched_declared_type() {
+        let mut encoded = transfer_tx().encode_envelope();
+        // Claim DeploySystem's type byte over an actual Transfer payload.
+        encoded[0] = TransactionPayload::DeploySystem { system_id: String::new(), yaml: None }.type_byte();
+        assert_eq!(
+            Transaction::decode_envelope(&encoded),
+            Err(EnvelopeError::TypeMismatch { declared: 0x01, actual: 0x04 })
+        );
+    }
 }
\ No newline at end of file
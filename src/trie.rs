@@ -0,0 +1,340 @@
+use crate::hash::keccak256;
+use crate::rlp;
+
+/// A secure (keccak256-keyed) Merkle Patricia Trie, modeled on the
+/// account/storage trie used by Ethereum-style clients. Keys passed in are
+/// already the 32-byte keccak256 hash of the real key (the "secure" step
+/// that keeps the trie balanced even against adversarial keys — see
+/// `smt::key_hash`, which this module reuses for that hashing); they're
+/// split into 64 nibbles for traversal. A node hashes as `keccak256` of its
+/// RLP encoding (`rlp::encode_list`/`encode_bytes`), tagged with a
+/// discriminant byte so a `Leaf` and an `Extension` (both two-field nodes)
+/// never collide.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrieNode {
+    Empty,
+    Leaf {
+        /// Remaining nibbles from this node down to the leaf's key.
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        /// Nibbles shared by every key under this node.
+        path: Vec<u8>,
+        child: [u8; 32],
+    },
+    Branch {
+        /// One child hash per nibble value (0-15).
+        children: [Option<[u8; 32]>; 16],
+        /// Populated only if some key's path ends exactly at this branch.
+        value: Option<Vec<u8>>,
+    },
+}
+
+const TAG_LEAF: u8 = 0x01;
+const TAG_EXTENSION: u8 = 0x02;
+const TAG_BRANCH: u8 = 0x03;
+
+impl TrieNode {
+    pub fn hash(&self) -> [u8; 32] {
+        keccak256(&self.encode())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            TrieNode::Empty => rlp::encode_list(&[]),
+
+            TrieNode::Leaf { path, value } => rlp::encode_list(&[
+                rlp::encode_bytes(&[TAG_LEAF]),
+                rlp::encode_bytes(path),
+                rlp::encode_bytes(value),
+            ]),
+
+            TrieNode::Extension { path, child } => rlp::encode_list(&[
+                rlp::encode_bytes(&[TAG_EXTENSION]),
+                rlp::encode_bytes(path),
+                rlp::encode_bytes(child),
+            ]),
+
+            TrieNode::Branch { children, value } => {
+                let mut items = vec![rlp::encode_bytes(&[TAG_BRANCH])];
+                for child in children {
+                    items.push(rlp::encode_bytes(child.as_ref().map(|h| h.as_slice()).unwrap_or(&[])));
+                }
+                items.push(rlp::encode_bytes(value.as_deref().unwrap_or(&[])));
+                rlp::encode_list(&items)
+            }
+        }
+    }
+}
+
+fn to_nibbles(key: &[u8; 32]) -> Vec<u8> {
+    let mut nibbles = Vec::with_capacity(64);
+    for byte in key {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    nibbles
+}
+
+/// A populated entry: its remaining nibble path and value bytes.
+type Entry = (Vec<u8>, Vec<u8>);
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+/// Builds the node for `entries`, which must be sorted ascending by nibble
+/// path. Recurses by path-compressing shared prefixes into `Extension`
+/// nodes and splitting on the next nibble at `Branch` nodes, exactly as a
+/// real Patricia trie does — just rebuilt from scratch each call rather
+/// than updated incrementally. There's no per-node hash cache keyed by
+/// trie position, so a caller that reruns `Trie::build` over a domain with
+/// even one dirty key still pays the full O(domain size) hashing cost, not
+/// just the O(changed keys) a true incremental trie would cost.
+fn build_node(entries: &[Entry]) -> TrieNode {
+    match entries.len() {
+        0 => TrieNode::Empty,
+        1 => TrieNode::Leaf {
+            path: entries[0].0.clone(),
+            value: entries[0].1.clone(),
+        },
+        _ => {
+            let first = &entries[0].0;
+            let shared = entries[1..]
+                .iter()
+                .fold(first.len(), |acc, (path, _)| acc.min(common_prefix_len(first, path)));
+
+            if shared > 0 {
+                let stripped: Vec<Entry> = entries
+                    .iter()
+                    .map(|(path, value)| (path[shared..].to_vec(), value.clone()))
+                    .collect();
+                let child = build_node(&stripped);
+                TrieNode::Extension { path: first[..shared].to_vec(), child: child.hash() }
+            } else {
+                branch_node(entries)
+            }
+        }
+    }
+}
+
+fn branch_node(entries: &[Entry]) -> TrieNode {
+    let mut children: [Option<[u8; 32]>; 16] = [None; 16];
+    let mut branch_value = None;
+    let mut i = 0;
+
+    while i < entries.len() {
+        let (path, value) = &entries[i];
+        if path.is_empty() {
+            branch_value = Some(value.clone());
+            i += 1;
+            continue;
+        }
+
+        let nibble = path[0] as usize;
+        let group_len = entries[i..]
+            .iter()
+            .take_while(|(p, _)| !p.is_empty() && p[0] as usize == nibble)
+            .count();
+        let group: Vec<Entry> = entries[i..i + group_len]
+            .iter()
+            .map(|(p, v)| (p[1..].to_vec(), v.clone()))
+            .collect();
+        children[nibble] = Some(build_node(&group).hash());
+        i += group_len;
+    }
+
+    TrieNode::Branch { children, value: branch_value }
+}
+
+/// Descends toward `target`, pushing every node visited so the returned
+/// path reads leaf-to-root (the order `Trie::prove`/`verify_proof` use).
+/// Stops early (without a terminal `Leaf`) when `target` diverges from
+/// every key under the current node — that's the exclusion-proof case.
+fn prove_node(entries: &[Entry], target: &[u8], path: &mut Vec<TrieNode>) -> [u8; 32] {
+    let node = build_node(entries);
+
+    match &node {
+        TrieNode::Empty => {}
+
+        TrieNode::Leaf { .. } => path.push(node.clone()),
+
+        TrieNode::Extension { path: ext_path, .. } => {
+            if target.starts_with(ext_path.as_slice()) {
+                let stripped: Vec<Entry> = entries
+                    .iter()
+                    .map(|(p, v)| (p[ext_path.len()..].to_vec(), v.clone()))
+                    .collect();
+                prove_node(&stripped, &target[ext_path.len()..], path);
+            }
+            path.push(node.clone());
+        }
+
+        TrieNode::Branch { .. } => {
+            if !target.is_empty() {
+                let nibble = target[0] as usize;
+                let group: Vec<Entry> = entries
+                    .iter()
+                    .filter(|(p, _)| !p.is_empty() && p[0] as usize == nibble)
+                    .map(|(p, v)| (p[1..].to_vec(), v.clone()))
+                    .collect();
+                if !group.is_empty() {
+                    prove_node(&group, &target[1..], path);
+                }
+            }
+            path.push(node.clone());
+        }
+    }
+
+    node.hash()
+}
+
+/// A domain's Merkle Patricia Trie, built fresh from its populated leaves.
+pub struct Trie {
+    entries: Vec<Entry>,
+}
+
+impl Trie {
+    /// Build from `(key_hash, value_bytes)` pairs.
+    pub fn build(leaves: Vec<([u8; 32], Vec<u8>)>) -> Self {
+        let mut entries: Vec<Entry> = leaves
+            .into_iter()
+            .map(|(key, value)| (to_nibbles(&key), value))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Trie { entries }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        build_node(&self.entries).hash()
+    }
+
+    /// The node path from leaf to root for `key_hash` — an inclusion proof
+    /// if it's populated, an exclusion proof (terminating at the node
+    /// where it diverges) otherwise.
+    pub fn prove(&self, key_hash: [u8; 32]) -> Vec<TrieNode> {
+        let target = to_nibbles(&key_hash);
+        let mut path = Vec::new();
+        prove_node(&self.entries, &target, &mut path);
+        path
+    }
+}
+
+/// Replays `proof` (as returned by `Trie::prove`, leaf-to-root order)
+/// against a known `root`, confirming `key_hash` maps to `value` (or to
+/// nothing, for an exclusion proof) without needing the rest of the trie.
+/// What a light client runs to validate a single entry off a block header.
+pub fn verify_proof(root: [u8; 32], key_hash: [u8; 32], value: Option<&[u8]>, proof: &[TrieNode]) -> bool {
+    if proof.is_empty() {
+        return value.is_none() && root == TrieNode::Empty.hash();
+    }
+
+    let target = to_nibbles(&key_hash);
+    let mut remaining = target.as_slice();
+    let mut expected_hash = root;
+
+    for node in proof.iter().rev() {
+        if node.hash() != expected_hash {
+            return false;
+        }
+
+        match node {
+            TrieNode::Empty => return value.is_none(),
+
+            TrieNode::Leaf { path, value: leaf_value } => {
+                return if remaining == path.as_slice() {
+                    value == Some(leaf_value.as_slice())
+                } else {
+                    value.is_none()
+                };
+            }
+
+            TrieNode::Extension { path, child } => {
+                if !remaining.starts_with(path.as_slice()) {
+                    return value.is_none();
+                }
+                remaining = &remaining[path.len()..];
+                expected_hash = *child;
+            }
+
+            TrieNode::Branch { children, value: branch_value } => {
+                if remaining.is_empty() {
+                    return value == branch_value.as_deref();
+                }
+                match children[remaining[0] as usize] {
+                    Some(child_hash) => {
+                        remaining = &remaining[1..];
+                        expected_hash = child_hash;
+                    }
+                    None => return value.is_none(),
+                }
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smt::{key_hash, DOMAIN_BALANCE};
+
+    #[test]
+    fn test_empty_trie_root_is_deterministic() {
+        let trie = Trie::build(vec![]);
+        assert_eq!(trie.root(), Trie::build(vec![]).root());
+    }
+
+    #[test]
+    fn test_single_entry_inclusion_proof_verifies() {
+        let key = key_hash(DOMAIN_BALANCE, b"alice:ETH");
+        let trie = Trie::build(vec![(key, b"1000".to_vec())]);
+        let root = trie.root();
+
+        let proof = trie.prove(key);
+        assert!(verify_proof(root, key, Some(b"1000"), &proof));
+    }
+
+    #[test]
+    fn test_exclusion_proof_for_absent_key_verifies() {
+        let present = key_hash(DOMAIN_BALANCE, b"alice:ETH");
+        let absent = key_hash(DOMAIN_BALANCE, b"bob:ETH");
+        let trie = Trie::build(vec![(present, b"1000".to_vec())]);
+        let root = trie.root();
+
+        let proof = trie.prove(absent);
+        assert!(verify_proof(root, absent, None, &proof));
+    }
+
+    #[test]
+    fn test_tampered_value_fails_verification() {
+        let key = key_hash(DOMAIN_BALANCE, b"alice:ETH");
+        let trie = Trie::build(vec![(key, b"1000".to_vec())]);
+        let root = trie.root();
+
+        let proof = trie.prove(key);
+        assert!(!verify_proof(root, key, Some(b"9999"), &proof));
+    }
+
+    #[test]
+    fn test_root_independent_of_insertion_order() {
+        let a = key_hash(DOMAIN_BALANCE, b"alice:ETH");
+        let b = key_hash(DOMAIN_BALANCE, b"bob:ETH");
+        let c = key_hash(DOMAIN_BALANCE, b"carol:ETH");
+
+        let trie1 = Trie::build(vec![
+            (a, b"1".to_vec()),
+            (b, b"2".to_vec()),
+            (c, b"3".to_vec()),
+        ]);
+        let trie2 = Trie::build(vec![
+            (c, b"3".to_vec()),
+            (a, b"1".to_vec()),
+            (b, b"2".to_vec()),
+        ]);
+
+        assert_eq!(trie1.root(), trie2.root());
+    }
+}
@@ -9,6 +9,16 @@ pub struct FvlSystem {
     pub rights: HashMap<String, Vec<String>>,
     pub time: Time,
     pub oracles: Vec<Oracle>,
+    /// Per-block net-flow limits, one entry per guarded asset (see
+    /// `state::State::check_circuit_breaker`). An asset with no entry here
+    /// is unlimited. Omitted entirely in existing YAML is equivalent to an
+    /// empty list.
+    #[serde(default)]
+    pub circuit_breakers: Vec<CircuitBreaker>,
+    /// Utilization-scaled fee curves, one entry per fee-charged asset (see
+    /// `FeeCurve`). An asset with no entry here is transferred fee-free.
+    #[serde(default)]
+    pub fees: Vec<FeeCurve>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -43,6 +53,12 @@ pub enum AccessRule {
         amount: u128,
         token: String,
     },
+    /// Gated by presenting a UCAN-style delegation chain (see
+    /// `crate::capability`) rooted at the system owner and granting
+    /// `ability` on this system.
+    Capability {
+        ability: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -140,6 +156,18 @@ pub enum Expression {
         #[serde(with = "u128_as_string")]
         value: u128,
     },
+    /// Like `PriceGt`, but matches against the oracle's time-weighted EMA
+    /// (see `state::OracleRecord`) instead of its raw last-written value, so
+    /// a single spot update can't flip the condition on its own. `period`
+    /// is not read when this expression is evaluated, and the EMA itself is
+    /// rolled forward with the oracle's own `Oracle::ema_period` (see
+    /// `State::set_oracle`), not with this value.
+    PriceGtEma {
+        oracle: String,
+        #[serde(with = "u128_as_string")]
+        value: u128,
+        period: u64,
+    },
     TimeGt {
         timestamp: u64,
     },
@@ -190,6 +218,25 @@ pub enum Action {
         from: String,
         to: String,
     },
+    /// Draws `amount` of `asset` out of the system's own reserve balance,
+    /// credits it to the triggering sender, runs `then` against the same
+    /// state, and requires both the sender's balance to have come back
+    /// down to at least `fee` below where it started (the loan plus `fee`
+    /// paid back) and the system's reserve to have been fully credited
+    /// back, before the
+    /// transaction commits (see `runtime::Runtime::apply_action`). Reverts
+    /// the whole transaction with `TxError::FlashLoanNotRepaid` otherwise —
+    /// since `apply_tx` discards state on any error, the revert is free.
+    FlashLoan {
+        asset: AssetType,
+        #[serde(with = "u128_as_string")]
+        amount: u128,
+        /// Extra amount beyond `amount` that must also be repaid. Omitted
+        /// (or zero) for a fee-free loan.
+        #[serde(with = "u128_as_string", default)]
+        fee: u128,
+        then: Vec<Action>,
+    },
     Pause,
     Unpause,
     Execute { function: String },
@@ -269,12 +316,56 @@ pub enum VestingValue {
     Milestone { conditions: Vec<String> },
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CircuitBreaker {
+    pub asset: AssetType,
+    /// Max absolute net inflow/outflow of `asset` this system may move
+    /// within a single block (summed across every action and interaction
+    /// that lands in it) before the triggering transaction is aborted with
+    /// `TxError::CircuitBreakerTripped`. `MaxAmount::None` means no cap.
+    pub max_delta: MaxAmount,
+}
+
+/// A utilization-scaled fee curve for `asset`, applied by
+/// `state::State::compute_fee` to the in-system `Action::Transfer` (see
+/// `runtime::Runtime::apply_action`). `rate = base_rate +
+/// utilization_slope * utilization / FEE_RATE_SCALE`, both rates expressed
+/// in parts-per-`FEE_RATE_SCALE` (see `state::FEE_RATE_SCALE`); the charged
+/// fee is `amount * rate / FEE_RATE_SCALE`, debited from the sender
+/// alongside `amount` and credited to `sink`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeCurve {
+    pub asset: AssetType,
+    #[serde(with = "u128_as_string")]
+    pub base_rate: u128,
+    #[serde(with = "u128_as_string", default)]
+    pub utilization_slope: u128,
+    /// Address the charged fee is credited to.
+    pub sink: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Oracle {
     pub name: String,
     #[serde(rename = "type")]
     pub oracle_type: String,
     pub source: String,
+    /// Blocks in the EMA smoothing window backing `state::OracleRecord`
+    /// (`alpha = 2/(period+1)`). Larger values smooth out spot updates
+    /// more aggressively; see `Expression::PriceGtEma`.
+    #[serde(default = "default_ema_period")]
+    pub ema_period: u64,
+}
+
+pub fn default_ema_period() -> u64 {
+    20
+}
+
+/// Default staleness window (in blocks) for a newly-registered oracle source
+/// (see `state::OracleSource`) when a `TransactionPayload::OracleUpdate`
+/// doesn't specify one.
+pub fn default_max_staleness() -> u64 {
+    50
 }
 
 /// Serde helper: serialize u128 as string for JSON compatibility
@@ -1,6 +1,34 @@
-use crate::types::{FvlSystem, AssetType, AccessRule};
+use crate::types::{
+    DistributionType, Expression, FvlSystem, AssetType, AccessRule, RecipientGroup, VestingValue,
+};
 use crate::parser::ParseError;
 use regex::Regex;
+use std::fmt;
+
+/// A single semantic violation found by [`Validator::analyze_semantics`].
+/// `field` is a dotted path to the offending value (e.g.
+/// `"rules.distribute.formula.thresholds"`) so callers can point a user at
+/// the exact spot in the YAML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub field: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
 
 pub struct Validator;
 
@@ -9,10 +37,198 @@ impl Validator {
         Self::validate_system_name(&system.system)?;
         Self::validate_asset_addresses(&system.pool.collect.what)?;
         Self::validate_access_rule_addresses(&system.pool.collect.from)?;
-        Self::validate_oracle_references(system)?;
-        
+
         Ok(())
     }
+
+    /// Deeper, cross-field semantic checks that go beyond structural/format
+    /// validation. Unlike `validate`, this never stops at the first problem:
+    /// every violation found is collected and returned so a user can fix
+    /// them all in one pass.
+    pub fn analyze_semantics(system: &FvlSystem) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        Self::check_oracle_references(system, &mut diagnostics);
+        Self::check_distribution(&system.rules.distribute.formula, &mut diagnostics);
+        Self::check_recipient_group(&system.rules.distribute.to, &mut diagnostics);
+        for (i, condition) in system.rules.conditions.iter().enumerate() {
+            Self::check_expression_counts(
+                &condition.if_expr,
+                &format!("rules.conditions[{}].if", i),
+                &mut diagnostics,
+            );
+        }
+        Self::check_collect_bounds(system, &mut diagnostics);
+        Self::check_vesting_schedule(system, &mut diagnostics);
+        Self::check_time_bounds(system, &mut diagnostics);
+        Self::check_circuit_breakers(system, &mut diagnostics);
+
+        diagnostics
+    }
+
+    /// Flags a `circuit_breakers` list with more than one entry for the
+    /// same asset — ambiguous, since `State::check_circuit_breaker` only
+    /// ever looks up the first matching entry.
+    fn check_circuit_breakers(system: &FvlSystem, diagnostics: &mut Vec<Diagnostic>) {
+        let mut seen: Vec<&AssetType> = Vec::new();
+        for (i, cb) in system.circuit_breakers.iter().enumerate() {
+            if seen.contains(&&cb.asset) {
+                diagnostics.push(Diagnostic::new(
+                    format!("circuit_breakers[{}].asset", i),
+                    "Duplicate circuit breaker entry for this asset".to_string(),
+                ));
+            } else {
+                seen.push(&cb.asset);
+            }
+        }
+    }
+
+    fn check_oracle_references(system: &FvlSystem, diagnostics: &mut Vec<Diagnostic>) {
+        let oracle_names: std::collections::HashSet<_> =
+            system.oracles.iter().map(|o| o.name.as_str()).collect();
+
+        for (i, condition) in system.rules.conditions.iter().enumerate() {
+            if let Expression::PriceLt { oracle, .. }
+            | Expression::PriceGt { oracle, .. }
+            | Expression::PriceEq { oracle, .. }
+            | Expression::PriceGtEma { oracle, .. } = &condition.if_expr
+            {
+                if !oracle_names.contains(oracle.as_str()) {
+                    diagnostics.push(Diagnostic::new(
+                        format!("rules.conditions[{}].if.oracle", i),
+                        format!("Oracle '{}' referenced but not defined", oracle),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_distribution(formula: &DistributionType, diagnostics: &mut Vec<Diagnostic>) {
+        if let DistributionType::Tiered { thresholds } = formula {
+            if thresholds.is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    "rules.distribute.formula.thresholds",
+                    "Tiered distribution must define at least one threshold",
+                ));
+            } else if !thresholds.windows(2).all(|w| w[0] < w[1]) {
+                diagnostics.push(Diagnostic::new(
+                    "rules.distribute.formula.thresholds",
+                    "Tiered distribution thresholds must be strictly increasing",
+                ));
+            }
+        }
+    }
+
+    fn check_recipient_group(to: &RecipientGroup, diagnostics: &mut Vec<Diagnostic>) {
+        if let RecipientGroup::TopN { count } = to {
+            if *count == 0 {
+                diagnostics.push(Diagnostic::new(
+                    "rules.distribute.to.count",
+                    "top_n count must be greater than zero",
+                ));
+            }
+        }
+    }
+
+    fn check_expression_counts(expr: &Expression, field: &str, diagnostics: &mut Vec<Diagnostic>) {
+        match expr {
+            Expression::HoldersCountGte { count } | Expression::HoldersCountLte { count } => {
+                if *count == 0 {
+                    diagnostics.push(Diagnostic::new(
+                        format!("{}.count", field),
+                        "holders count must be greater than zero",
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_collect_bounds(system: &FvlSystem, diagnostics: &mut Vec<Diagnostic>) {
+        let collect = &system.pool.collect;
+
+        let min = match &collect.min {
+            crate::types::Amount::Zero => 0u128,
+            crate::types::Amount::Value { amount } => *amount,
+        };
+        let max = match &collect.max {
+            crate::types::MaxAmount::None => None,
+            crate::types::MaxAmount::Value { amount } => Some(*amount),
+        };
+        let cap = match &collect.cap {
+            crate::types::CapAmount::None => None,
+            crate::types::CapAmount::Value { amount } => Some(*amount),
+        };
+
+        if let Some(max) = max {
+            if min > max {
+                diagnostics.push(Diagnostic::new(
+                    "pool.collect.max",
+                    format!("min ({}) must not exceed max ({})", min, max),
+                ));
+            }
+        }
+
+        if let (Some(max), Some(cap)) = (max, cap) {
+            if max > cap {
+                diagnostics.push(Diagnostic::new(
+                    "pool.collect.cap",
+                    format!("max ({}) must not exceed cap ({})", max, cap),
+                ));
+            }
+        }
+    }
+
+    /// Total elapsed time implied by a vesting schedule, used to bound
+    /// `cliffs` against it. `None` means the vesting type has no single
+    /// well-defined duration (e.g. milestone-based vesting).
+    fn vesting_duration(vesting: &VestingValue) -> Option<u64> {
+        match vesting {
+            VestingValue::None | VestingValue::Milestone { .. } => None,
+            VestingValue::Linear { duration } | VestingValue::Cliff { duration } => Some(*duration),
+            VestingValue::Graded { schedule } => schedule.iter().copied().max(),
+        }
+    }
+
+    fn check_vesting_schedule(system: &FvlSystem, diagnostics: &mut Vec<Diagnostic>) {
+        if let VestingValue::Graded { schedule } = &system.time.vesting {
+            if !schedule.windows(2).all(|w| w[0] <= w[1]) {
+                diagnostics.push(Diagnostic::new(
+                    "time.vesting.schedule",
+                    "Graded vesting schedule must be monotonically non-decreasing",
+                ));
+            }
+        }
+
+        if let Some(cliffs) = system.time.cliffs {
+            if let Some(duration) = Self::vesting_duration(&system.time.vesting) {
+                if cliffs > duration {
+                    diagnostics.push(Diagnostic::new(
+                        "time.cliffs",
+                        format!(
+                            "cliffs ({}) must not exceed the vesting duration ({})",
+                            cliffs, duration
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_time_bounds(system: &FvlSystem, diagnostics: &mut Vec<Diagnostic>) {
+        if let (
+            crate::types::TimeValue::Timestamp { value: start },
+            crate::types::TimeValue::Timestamp { value: end },
+        ) = (&system.time.start, &system.time.end)
+        {
+            if start > end {
+                diagnostics.push(Diagnostic::new(
+                    "time.end",
+                    format!("start ({}) must not be after end ({})", start, end),
+                ));
+            }
+        }
+    }
     
     fn validate_system_name(name: &str) -> Result<(), ParseError> {
         if name.is_empty() {
@@ -69,29 +285,15 @@ impl Validator {
                 Ok(())
             }
             AccessRule::MinBalance { token, .. } => Self::validate_ethereum_address(token),
-        }
-    }
-    
-    fn validate_oracle_references(system: &FvlSystem) -> Result<(), ParseError> {
-        let oracle_names: std::collections::HashSet<_> = 
-            system.oracles.iter().map(|o| o.name.as_str()).collect();
-        
-        for condition in &system.rules.conditions {
-            match &condition.if_expr {
-                crate::types::Expression::PriceLt { oracle, .. } |
-                crate::types::Expression::PriceGt { oracle, .. } |
-                crate::types::Expression::PriceEq { oracle, .. } => {
-                    if !oracle_names.contains(oracle.as_str()) {
-                        return Err(ParseError::ValidationError(
-                            format!("Oracle '{}' referenced but not defined", oracle)
-                        ));
-                    }
+            AccessRule::Capability { ability } => {
+                if ability.is_empty() {
+                    return Err(ParseError::ValidationError(
+                        "Capability ability cannot be empty".to_string()
+                    ));
                 }
-                _ => {}
+                Ok(())
             }
         }
-        
-        Ok(())
     }
 }
 
@@ -130,6 +332,8 @@ mod tests {
                 cliffs: None,
             },
             oracles: vec![],
+            circuit_breakers: vec![],
+            fees: vec![],
         }
     }
 
@@ -159,6 +363,15 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_empty_capability_ability() {
+        let mut system = create_minimal_system("Test");
+        system.pool.collect.from = AccessRule::Capability { ability: "".to_string() };
+
+        let result = Validator::validate(&system);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_validate_undefined_oracle() {
         let mut system = create_minimal_system("Test");
@@ -169,8 +382,109 @@ mod tests {
             },
             then: Action::Pause,
         });
-        
-        let result = Validator::validate(&system);
-        assert!(result.is_err());
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].field.contains("oracle"));
+    }
+
+    #[test]
+    fn test_analyze_semantics_empty_tiered_thresholds() {
+        let mut system = create_minimal_system("Test");
+        system.rules.distribute.formula = DistributionType::Tiered { thresholds: vec![] };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("thresholds")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_non_increasing_tiered_thresholds() {
+        let mut system = create_minimal_system("Test");
+        system.rules.distribute.formula = DistributionType::Tiered {
+            thresholds: vec![100, 100, 50],
+        };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("thresholds")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_zero_top_n_count() {
+        let mut system = create_minimal_system("Test");
+        system.rules.distribute.to = RecipientGroup::TopN { count: 0 };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("count")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_zero_holders_count() {
+        let mut system = create_minimal_system("Test");
+        system.rules.conditions.push(Condition {
+            if_expr: Expression::HoldersCountGte { count: 0 },
+            then: Action::Pause,
+        });
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("count")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_collect_bounds_violation() {
+        let mut system = create_minimal_system("Test");
+        system.pool.collect.min = Amount::Value { amount: 100 };
+        system.pool.collect.max = MaxAmount::Value { amount: 50 };
+        system.pool.collect.cap = CapAmount::Value { amount: 10 };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_analyze_semantics_valid_collect_bounds() {
+        let mut system = create_minimal_system("Test");
+        system.pool.collect.min = Amount::Value { amount: 10 };
+        system.pool.collect.max = MaxAmount::Value { amount: 50 };
+        system.pool.collect.cap = CapAmount::Value { amount: 100 };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_semantics_non_monotonic_graded_schedule() {
+        let mut system = create_minimal_system("Test");
+        system.time.vesting = VestingValue::Graded {
+            schedule: vec![100, 50, 200],
+        };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("schedule")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_cliffs_exceed_vesting_duration() {
+        let mut system = create_minimal_system("Test");
+        system.time.vesting = VestingValue::Linear { duration: 100 };
+        system.time.cliffs = Some(200);
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("cliffs")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_time_start_after_end() {
+        let mut system = create_minimal_system("Test");
+        system.time.start = TimeValue::Timestamp { value: 200 };
+        system.time.end = TimeValue::Timestamp { value: 100 };
+
+        let diagnostics = Validator::analyze_semantics(&system);
+        assert!(diagnostics.iter().any(|d| d.field.contains("time.end")));
+    }
+
+    #[test]
+    fn test_analyze_semantics_clean_system_has_no_diagnostics() {
+        let system = create_minimal_system("Test");
+        assert!(Validator::analyze_semantics(&system).is_empty());
     }
 }
\ No newline at end of file